@@ -45,6 +45,41 @@ async fn main() {
             "/PolygonToCellMapConcaveHull",
             post(|e| polygon_handler_contours_concave_hull(e, ps::bydistance_contours)),
         )
+        .route(
+            "/PolygonToCellMapPath",
+            post(|e| polygon_handler_json(e, ps::bypath)),
+        )
+        .route(
+            "/PolygonToCellMapDarp",
+            post(|e| polygon_handler_json(e, ps::darp)),
+        )
+        .route(
+            "/PolygonToCellMapCentroidalVoronoi",
+            post(|e| polygon_handler_json(e, ps::centroidal_voronoi)),
+        )
+        .route(
+            "/PolygonToOccupancyGrid",
+            post(|e| polygon_handler_occupancy_grid(e, ps::bydistance)),
+        )
+        .route(
+            "/FrontierTargets",
+            post(|e| frontier_targets_json(e, ps::bydistance)),
+        )
+        .route(
+            "/CoveragePath",
+            post(|e| coverage_path_json(e, ps::bydistance)),
+        )
+        .route("/Evaluate", post(|e| evaluate_json(e, ps::bydistance)))
+        .route("/evaluate", post(|e| evaluate_json(e, ps::bydistance)))
+        .route("/Consistency", post(consistency_json))
+        .route(
+            "/Rendezvous",
+            post(|e| rendezvous_json(e, ps::bydistance)),
+        )
+        .route(
+            "/PolygonToCellMapMultipart",
+            post(|m| polygon_handler_multipart(m, ps::bydistance)),
+        )
         .route(
             "/PolygonToCellMapShm",
             post(|e| polygon_handler_shm(e, ps::bydistance)),
@@ -3,3 +3,60 @@ pub use voronoi::*;
 
 mod partitioning_factors;
 pub use partitioning_factors::*;
+
+mod context;
+pub use context::*;
+
+mod tie_breaking;
+pub use tie_breaking::*;
+
+mod grid;
+pub use grid::*;
+
+mod ownership;
+pub use ownership::*;
+
+mod connectivity;
+pub use connectivity::*;
+
+mod darp;
+pub use darp::*;
+
+mod traversal;
+pub use traversal::*;
+
+mod blockage;
+pub use blockage::*;
+
+mod exploration;
+pub use exploration::*;
+
+mod targets;
+pub use targets::*;
+
+mod coverage;
+pub use coverage::*;
+
+mod metrics;
+pub use metrics::*;
+
+mod consistency;
+pub use consistency::*;
+
+mod failure;
+pub use failure::*;
+
+mod joining;
+pub use joining::*;
+
+mod borders;
+pub use borders::*;
+
+mod rendezvous;
+pub use rendezvous::*;
+
+mod buffer;
+pub use buffer::*;
+
+#[cfg(test)]
+mod testing;
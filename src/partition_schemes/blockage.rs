@@ -0,0 +1,25 @@
+//! This module provides the [`Blockage`] of cells which may not be entered at
+//! all, regardless of which robot asks.
+
+/// Why a cell is impassable.
+///
+/// Unlike explored areas, which merely do not need to be covered anymore,
+/// blocked cells are never assigned and cannot be travelled through.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Blockage {
+    /// A physical obstacle, e.g. a building.
+    Obstacle,
+    /// An area robots are forbidden to enter, e.g. restricted airspace.
+    NoGo,
+}
+
+impl Blockage {
+    /// The label used in place of the cell's [`local_robot_map::LocationType`]
+    /// in the output.
+    pub fn label(&self) -> &'static str {
+        match self {
+            Blockage::Obstacle => "Obstacle",
+            Blockage::NoGo => "NoGo",
+        }
+    }
+}
@@ -0,0 +1,197 @@
+//! This module finds the borders between the regions of the robots.
+//!
+//! The borders are derived from the [`Ownership`] rather than from
+//! [`LocationType::Frontier`], which only marks the border of my region and
+//! does not tell who is on the other side.
+//!
+//! [`LocationType::Frontier`]: local_robot_map::LocationType::Frontier
+
+use std::collections::{BTreeMap, HashMap};
+
+use local_robot_map::RealWorldLocation;
+
+use super::{GridFrame, Ownership};
+
+/// The border shared by two neighbouring robots.
+#[derive(Debug, Clone)]
+pub struct Border {
+    /// The indices of both robots, the lower one first.
+    pub robots: (usize, usize),
+    /// The connected pieces of the border, each ordered from one end to the
+    /// other (or around, if it is closed).
+    pub polylines: Vec<Vec<RealWorldLocation>>,
+    pub length: f64,
+    /// The point halfway along the longest piece.
+    pub midpoint: RealWorldLocation,
+}
+
+/// Pairs of adjacent cells (sharing an edge) owned by two different robots,
+/// grouped by the robots. The cell of the lower robot comes first and neither
+/// cell is owned by both robots.
+pub(crate) type BorderCells = BTreeMap<(usize, usize), Vec<((usize, usize), (usize, usize))>>;
+
+pub(crate) fn border_cells(ownership: &Ownership, frame: &GridFrame) -> BorderCells {
+    let mut pairs: BorderCells = BTreeMap::new();
+    for row in 0..frame.rows() {
+        for col in 0..frame.cols() {
+            let here = (row, col);
+            let next = [(row + 1, col), (row, col + 1)];
+            for there in next
+                .into_iter()
+                .filter(|&(r, c)| r < frame.rows() && c < frame.cols())
+            {
+                for &a in ownership.owners(here) {
+                    for &b in ownership.owners(there) {
+                        if a == b
+                            || ownership.is_owned_by(there, a)
+                            || ownership.is_owned_by(here, b)
+                        {
+                            continue;
+                        }
+                        let (robots, cells) = if a < b {
+                            ((a, b), (here, there))
+                        } else {
+                            ((b, a), (there, here))
+                        };
+                        pairs.entry(robots).or_default().push(cells);
+                    }
+                }
+            }
+        }
+    }
+    pairs
+}
+
+/// The border of every pair of robots whose regions touch.
+pub fn borders(ownership: &Ownership, frame: &GridFrame) -> Vec<Border> {
+    border_cells(ownership, frame)
+        .into_iter()
+        .map(|(robots, cells)| {
+            // The side between two cells, as indices of its corners
+            let mut corners: HashMap<(usize, usize), Vec<(usize, usize)>> = HashMap::new();
+            for (a, b) in cells {
+                let (low, high) = (a.min(b), a.max(b));
+                let side = if low.0 == high.0 {
+                    ((high.0, high.1), (high.0 + 1, high.1))
+                } else {
+                    ((high.0, high.1), (high.0, high.1 + 1))
+                };
+                corners.entry(side.0).or_default().push(side.1);
+                corners.entry(side.1).or_default().push(side.0);
+            }
+
+            let mut pieces = Vec::new();
+            while let Some(start) = corners
+                .iter()
+                .filter(|(_, next)| !next.is_empty())
+                .min_by_key(|(&corner, next)| (next.len() % 2 == 0, corner))
+                .map(|(&corner, _)| corner)
+            {
+                // Walk along unused sides, starting at an open end if any
+                let mut piece = vec![start];
+                let mut current = start;
+                while let Some(next) = corners.get_mut(&current).and_then(|next| next.pop()) {
+                    let back = corners.get_mut(&next).expect("Sides are stored both ways");
+                    let position = back
+                        .iter()
+                        .position(|&corner| corner == current)
+                        .expect("Sides are stored both ways");
+                    back.swap_remove(position);
+                    piece.push(next);
+                    current = next;
+                }
+                pieces.push(
+                    piece
+                        .into_iter()
+                        .map(|(row, col)| frame.corner(row, col))
+                        .collect::<Vec<_>>(),
+                );
+            }
+
+            let lengths: Vec<f64> = pieces
+                .iter()
+                .map(Vec::as_slice)
+                .map(polyline_length)
+                .collect();
+            let longest = (0..pieces.len())
+                .max_by(|&a, &b| lengths[a].total_cmp(&lengths[b]))
+                .expect("Touching regions share a side");
+            Border {
+                robots,
+                midpoint: halfway(&pieces[longest], lengths[longest]),
+                length: lengths.iter().sum(),
+                polylines: pieces,
+            }
+        })
+        .collect()
+}
+
+fn polyline_length(points: &[RealWorldLocation]) -> f64 {
+    points
+        .windows(2)
+        .map(|pair| (pair[1].x() - pair[0].x()).hypot(pair[1].y() - pair[0].y()))
+        .sum()
+}
+
+/// The point halfway along a polyline of the given `length`.
+fn halfway(points: &[RealWorldLocation], length: f64) -> RealWorldLocation {
+    let mut remaining = length / 2.0;
+    for pair in points.windows(2) {
+        let (a, b) = (&pair[0], &pair[1]);
+        let step = (b.x() - a.x()).hypot(b.y() - a.y());
+        if remaining <= step && step > 0.0 {
+            let t = remaining / step;
+            return RealWorldLocation::from_xyz(
+                a.x() + t * (b.x() - a.x()),
+                a.y() + t * (b.y() - a.y()),
+                a.z(),
+            );
+        }
+        remaining -= step;
+    }
+    points[points.len() - 1].clone()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ps::testing::ownership_of;
+
+    #[test]
+    fn halves_share_a_straight_border() {
+        let frame = GridFrame::matrix((4, 4));
+        let ownership = ownership_of((4, 4), |(_, col)| Some(usize::from(col >= 2)));
+
+        let borders = borders(&ownership, &frame);
+
+        assert_eq!(borders.len(), 1);
+        let border = &borders[0];
+        assert_eq!(border.robots, (0, 1));
+        assert_eq!(border.polylines.len(), 1);
+        assert!((border.length - 4.0).abs() < 1e-9);
+        assert!((border.midpoint.x() - 1.5).abs() < 1e-9);
+        assert!((border.midpoint.y() - 1.5).abs() < 1e-9);
+    }
+
+    #[test]
+    fn shared_cells_are_no_border() {
+        let frame = GridFrame::matrix((1, 3));
+        let mut ownership = ownership_of((1, 3), |(_, col)| Some(usize::from(col >= 1)));
+        ownership.add((0, 1), 0);
+
+        assert!(borders(&ownership, &frame).is_empty());
+    }
+
+    #[test]
+    fn separate_pieces_add_up() {
+        // Robot 1 owns the middle row, splitting the border with robot 0
+        let frame = GridFrame::matrix((3, 2));
+        let ownership = ownership_of((3, 2), |(row, col)| Some(usize::from(row == 1 || col == 1)));
+
+        let borders = borders(&ownership, &frame);
+
+        assert_eq!(borders.len(), 1);
+        assert_eq!(borders[0].polylines.len(), 2);
+        assert!((borders[0].length - 4.0).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,125 @@
+//! This module extends a robot's region into those of its neighbours.
+//!
+//! Localization drift may otherwise leave gaps along the borders. The extension
+//! (the *buffer*) is kept apart from the robot's own (*core*) cells, so these
+//! can be covered first.
+
+use ndarray::Array2;
+
+use super::{Context, GridFrame, Neighbourhood, Ownership};
+
+/// The cells owned by other robots whose centre lies within `distance` of a
+/// cell of `robot`, and which `robot` may enter.
+pub fn buffer_zone(
+    ownership: &Ownership,
+    frame: &GridFrame,
+    context: &Context,
+    robot: usize,
+    distance: f64,
+) -> Array2<bool> {
+    let mut buffer = Array2::from_elem((frame.rows(), frame.cols()), false);
+    if !distance.is_finite() || distance <= 0.0 {
+        return buffer;
+    }
+    let (size_x, size_y) = frame.cell_size();
+    let reach = (
+        (distance / size_y).floor() as usize,
+        (distance / size_x).floor() as usize,
+    );
+
+    // Only the core cells along the border can be closest to any other cell
+    let core = ownership.cells_of(robot);
+    let border = core.iter().filter(|&&index| {
+        frame
+            .neighbours(index, Neighbourhood::Four)
+            .any(|neighbour| !ownership.is_owned_by(neighbour, robot))
+    });
+    for &(row, col) in border {
+        let rows = row.saturating_sub(reach.0)..(row + reach.0 + 1).min(frame.rows());
+        for other_row in rows {
+            let cols = col.saturating_sub(reach.1)..(col + reach.1 + 1).min(frame.cols());
+            for other_col in cols {
+                let index = (other_row, other_col);
+                let (d_row, d_col) = (other_row.abs_diff(row), other_col.abs_diff(col));
+                if !buffer[index]
+                    && (d_row as f64 * size_y).hypot(d_col as f64 * size_x) <= distance
+                    && !ownership.owners(index).is_empty()
+                    && !ownership.is_owned_by(index, robot)
+                    && context.allows(index, robot)
+                {
+                    buffer[index] = true;
+                }
+            }
+        }
+    }
+    buffer
+}
+
+#[cfg(test)]
+mod tests {
+    use local_robot_map::RealWorldLocation;
+
+    use super::*;
+    use crate::ps::testing::ownership_of;
+
+    fn halves() -> (GridFrame, Ownership, Context) {
+        let frame = GridFrame::matrix((4, 4));
+        let ownership = ownership_of((4, 4), |(_, col)| Some(usize::from(col >= 2)));
+        let positions = [frame.location(0, 0), frame.location(0, 3)];
+        let context = Context::new(vec![None; 2], positions.iter().collect());
+        (frame, ownership, context)
+    }
+
+    /// The columns holding buffer cells, all of whose rows are in the buffer.
+    fn columns(buffer: &Array2<bool>) -> Vec<usize> {
+        (0..buffer.ncols())
+            .filter(|&col| buffer.column(col).iter().all(|&cell| cell))
+            .collect()
+    }
+
+    #[test]
+    fn buffer_reaches_into_the_neighbour() {
+        let (frame, ownership, context) = halves();
+
+        let near = buffer_zone(&ownership, &frame, &context, 0, 1.5);
+        let far = buffer_zone(&ownership, &frame, &context, 0, 2.0);
+
+        assert_eq!(columns(&near), vec![2]);
+        assert_eq!(near.iter().filter(|&&cell| cell).count(), 4);
+        assert_eq!(columns(&far), vec![2, 3]);
+    }
+
+    #[test]
+    fn no_buffer_without_distance() {
+        let (frame, ownership, context) = halves();
+
+        let buffer = buffer_zone(&ownership, &frame, &context, 0, 0.0);
+
+        assert!(buffer.iter().all(|&cell| !cell));
+    }
+
+    #[test]
+    fn buffer_only_holds_cells_the_robot_may_enter() {
+        let (frame, ownership, context) = halves();
+        let mut access = vec![Array2::from_elem((4, 4), true); 2];
+        access[0][[0, 2]] = false;
+        let context = context.with_access(Some(access));
+
+        let buffer = buffer_zone(&ownership, &frame, &context, 0, 1.0);
+
+        assert!(!buffer[[0, 2]]);
+        assert_eq!(buffer.iter().filter(|&&cell| cell).count(), 3);
+    }
+
+    #[test]
+    fn unowned_cells_are_no_buffer() {
+        let frame = GridFrame::matrix((1, 3));
+        let ownership = ownership_of((1, 3), |(_, col)| (col != 1).then_some(col / 2));
+        let positions = [RealWorldLocation::from_xyz(0.0, 0.0, 0.0)];
+        let context = Context::new(vec![None], positions.iter().collect());
+
+        let buffer = buffer_zone(&ownership, &frame, &context, 0, 1.0);
+
+        assert!(buffer.iter().all(|&cell| !cell));
+    }
+}
@@ -0,0 +1,286 @@
+//! A post-processing pass ensuring every robot's region is a single connected
+//! component which the robot can reach without crossing another region.
+//!
+//! Any partitioning scheme may produce *islands*, e.g. when holes from explored
+//! polygons cut a Voronoi region in pieces. Only the component containing the
+//! robot is kept (one per area if the map consists of several areas). The
+//! remaining islands are either handed to a neighbouring robot which can reach
+//! them, or flagged.
+
+use std::collections::BTreeMap;
+
+use ndarray::Array2;
+use serde::Deserialize;
+
+use crate::Map;
+
+use super::{robots, Context, GridFrame, Neighbourhood, Ownership};
+
+/// Configuration of the connectivity enforcement.
+#[derive(Deserialize, Debug, Default, Clone, Copy)]
+pub struct Connectivity {
+    #[serde(default)]
+    pub neighbourhood: Neighbourhood,
+    #[serde(default)]
+    pub orphans: Orphans,
+}
+
+/// What to do with islands which are not connected to their robot.
+#[derive(Deserialize, Debug, Default, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum Orphans {
+    /// Hand the island to the neighbouring robot sharing the longest border
+    /// with it. Islands without any neighbouring robot which may enter all of
+    /// their cells are flagged.
+    #[default]
+    Reassign,
+    /// Remove the island from its robot and flag it.
+    Flag,
+}
+
+/// A group of connected cells which is not reachable by its previous owner.
+#[derive(Debug, Clone)]
+pub struct Island {
+    /// The robot which owned the island before enforcing connectivity.
+    pub robot: usize,
+    pub cells: Vec<(usize, usize)>,
+}
+
+/// Enforce connectivity of every robot's region in `ownership`.
+///
+/// Returns the islands which could not be handed to another robot and are
+/// hence owned by nobody anymore.
+pub fn enforce_connectivity(
+    ownership: &mut Ownership,
+    map: &Map,
+    config: &Connectivity,
+    context: &Context,
+) -> Vec<Island> {
+    let frame = GridFrame::new(map.map());
+    let mut islands: Vec<Island> = Vec::new();
+
+    for (robot, location) in robots(map).map(|r| r.location()).enumerate() {
+        // A region spanning several areas cannot be connected, hence the
+        // component closest to the robot is kept in every area.
+        let mut by_area: BTreeMap<Option<usize>, Vec<Vec<(usize, usize)>>> = BTreeMap::new();
+        for part in components(&frame, &ownership.cells_of(robot), config.neighbourhood) {
+            by_area.entry(context.area(part[0])).or_default().push(part);
+        }
+
+        for mut parts in by_area.into_values() {
+            let main = match frame.index(location) {
+                Some(origin) => closest_component(&parts, origin),
+                None => 0,
+            };
+            parts.remove(main);
+
+            for cells in parts {
+                for &cell in &cells {
+                    ownership.remove(cell, robot);
+                }
+                islands.push(Island { robot, cells });
+            }
+        }
+    }
+
+    if config.orphans == Orphans::Flag {
+        return islands;
+    }
+
+    // Handing over an island grows the recipient's region, which may in turn
+    // touch further islands. Hence we repeat until nothing changes anymore.
+    let mut changed = true;
+    while changed {
+        changed = false;
+        let mut remaining = Vec::new();
+        for island in islands {
+            match recipient(&frame, ownership, &island, config.neighbourhood, context) {
+                Some(robot) => {
+                    for &cell in &island.cells {
+                        ownership.add(cell, robot);
+                    }
+                    changed = true;
+                }
+                None => remaining.push(island),
+            }
+        }
+        islands = remaining;
+    }
+
+    islands
+}
+
+/// Split the cells into connected components.
+pub(crate) fn components(
+    frame: &GridFrame,
+    cells: &[(usize, usize)],
+    neighbourhood: Neighbourhood,
+) -> Vec<Vec<(usize, usize)>> {
+    let mut member = Array2::from_elem((frame.rows(), frame.cols()), false);
+    for &(row, col) in cells {
+        member[[row, col]] = true;
+    }
+
+    let mut components = Vec::new();
+    for &start in cells {
+        if !member[[start.0, start.1]] {
+            continue;
+        }
+        member[[start.0, start.1]] = false;
+
+        let mut component = vec![start];
+        let mut next = 0;
+        while next < component.len() {
+            for (row, col) in frame.neighbours(component[next], neighbourhood) {
+                if member[[row, col]] {
+                    member[[row, col]] = false;
+                    component.push((row, col));
+                }
+            }
+            next += 1;
+        }
+        component.sort_unstable();
+        components.push(component);
+    }
+
+    components
+}
+
+/// The index of the component containing `origin` or, if there is none, the
+/// one closest to it.
+pub(crate) fn closest_component(
+    components: &[Vec<(usize, usize)>],
+    origin: (usize, usize),
+) -> usize {
+    let distance = |&(row, col): &(usize, usize)| -> usize {
+        row.abs_diff(origin.0).pow(2) + col.abs_diff(origin.1).pow(2)
+    };
+
+    components
+        .iter()
+        .enumerate()
+        .min_by_key(|(_, cells)| cells.iter().map(distance).min())
+        .map(|(index, _)| index)
+        .expect("There is at least one component")
+}
+
+/// The robot sharing the longest border with the island, ties being settled
+/// by the robots' ranks. Only robots allowed on every cell of the island are
+/// considered.
+fn recipient(
+    frame: &GridFrame,
+    ownership: &Ownership,
+    island: &Island,
+    neighbourhood: Neighbourhood,
+    context: &Context,
+) -> Option<usize> {
+    let mut contacts = vec![0usize; context.ranks().len()];
+    for &cell in &island.cells {
+        for neighbour in frame.neighbours(cell, neighbourhood) {
+            for &robot in ownership.owners(neighbour) {
+                if robot != island.robot {
+                    contacts[robot] += 1;
+                }
+            }
+        }
+    }
+
+    contacts
+        .iter()
+        .enumerate()
+        .filter(|&(robot, &count)| {
+            count > 0 && island.cells.iter().all(|&cell| context.allows(cell, robot))
+        })
+        .max_by(|(a, count_a), (b, count_b)| {
+            count_a
+                .cmp(count_b)
+                .then_with(|| context.ranks()[*b].cmp(&context.ranks()[*a]))
+        })
+        .map(|(robot, _)| robot)
+}
+
+#[cfg(test)]
+mod tests {
+    use ndarray::Array2;
+
+    use super::*;
+    use crate::ps::testing::{context_of, ownership_of, square_map};
+
+    #[test]
+    fn components_depend_on_the_neighbourhood() {
+        let frame = GridFrame::matrix((3, 3));
+        let diagonal = [(0, 0), (1, 1), (2, 2)];
+
+        assert_eq!(components(&frame, &diagonal, Neighbourhood::Four).len(), 3);
+        assert_eq!(
+            components(&frame, &diagonal, Neighbourhood::Eight),
+            vec![diagonal.to_vec()]
+        );
+    }
+
+    #[test]
+    fn closest_component_prefers_the_one_containing_the_origin() {
+        let parts = vec![vec![(0, 0)], vec![(2, 2), (2, 3)]];
+
+        assert_eq!(closest_component(&parts, (2, 3)), 1);
+        assert_eq!(closest_component(&parts, (1, 0)), 0);
+    }
+
+    /// Robot `0` owns the two leftmost columns plus an island surrounded by
+    /// robot `1`, which owns the rest.
+    fn island() -> (Map, Ownership) {
+        let map = square_map(5.0, &[(0, 0), (4, 4)]);
+        let frame = GridFrame::new(map.map());
+        let ownership = ownership_of((frame.rows(), frame.cols()), |(row, col)| {
+            Some(usize::from(col >= 2 && (row, col) != (2, 3)))
+        });
+        (map, ownership)
+    }
+
+    #[test]
+    fn islands_are_handed_to_the_neighbour() {
+        let (map, mut ownership) = island();
+        let context = context_of(&map);
+
+        let orphans =
+            enforce_connectivity(&mut ownership, &map, &Connectivity::default(), &context);
+
+        assert!(orphans.is_empty());
+        assert_eq!(ownership.owners((2, 3)), &[1]);
+    }
+
+    #[test]
+    fn islands_are_flagged_if_requested() {
+        let (map, mut ownership) = island();
+        let context = context_of(&map);
+        let config = Connectivity {
+            orphans: Orphans::Flag,
+            ..Connectivity::default()
+        };
+
+        let orphans = enforce_connectivity(&mut ownership, &map, &config, &context);
+
+        assert_eq!(orphans.len(), 1);
+        assert_eq!(
+            (orphans[0].robot, orphans[0].cells.clone()),
+            (0, vec![(2, 3)])
+        );
+        assert!(ownership.owners((2, 3)).is_empty());
+    }
+
+    #[test]
+    fn islands_are_flagged_if_the_neighbour_may_not_enter() {
+        let (map, mut ownership) = island();
+        let frame = GridFrame::new(map.map());
+        let mut denied = Array2::from_elem((frame.rows(), frame.cols()), true);
+        denied[[2, 3]] = false;
+        let access = vec![Array2::from_elem(denied.dim(), true), denied];
+        let context = context_of(&map).with_access(Some(access));
+
+        let orphans =
+            enforce_connectivity(&mut ownership, &map, &Connectivity::default(), &context);
+
+        assert_eq!(orphans.len(), 1);
+        assert!(ownership.owners((2, 3)).is_empty());
+    }
+}
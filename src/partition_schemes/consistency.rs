@@ -0,0 +1,180 @@
+//! This module checks whether the regions which the robots computed on their
+//! own fit together.
+//!
+//! Every robot calls the API with itself as `me`, so each response only holds
+//! its own region. Since the robots' views of each other differ slightly, the
+//! regions may overlap or leave gaps in between.
+
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, VecDeque};
+
+use ndarray::Array2;
+use serde::Serialize;
+
+use super::{GridFrame, Neighbourhood, Ownership};
+
+/// Two robots claiming the same cells.
+#[derive(Serialize, Debug, Clone, Copy)]
+pub struct Conflict {
+    pub robots: (usize, usize),
+    /// The number of cells both robots claim.
+    pub cells: usize,
+}
+
+/// How well the regions of the robots fit together.
+#[derive(Debug, Clone)]
+pub struct Consistency {
+    /// Cells claimed by several robots, along with these robots.
+    pub overlapping: Vec<((usize, usize), Vec<usize>)>,
+    /// Cells to be covered which nobody claims.
+    pub orphaned: Vec<(usize, usize)>,
+    pub conflicts: Vec<Conflict>,
+    /// A partition without overlaps and, as far as possible, gaps.
+    pub reconciled: Option<Ownership>,
+}
+
+/// Compare the `claims` of the robots, each holding the cells of a robot's
+/// region. Only the `coverable` cells can be orphaned.
+///
+/// If requested, the claims are also reconciled: every overlapping cell goes to
+/// the claimant with most claimed neighbours (the lowest index on ties), and
+/// every orphaned cell to the closest region reachable across coverable cells.
+pub fn check_consistency(
+    frame: &GridFrame,
+    claims: &[Array2<bool>],
+    coverable: &Array2<bool>,
+    reconcile: bool,
+) -> Consistency {
+    let regions: Vec<Vec<(usize, usize)>> = claims
+        .iter()
+        .map(|claim| {
+            claim
+                .indexed_iter()
+                .filter(|(_, claimed)| **claimed)
+                .map(|(index, _)| index)
+                .collect()
+        })
+        .collect();
+    let ownership = Ownership::from_regions((frame.rows(), frame.cols()), &regions);
+
+    let mut overlapping = Vec::new();
+    let mut orphaned = Vec::new();
+    let mut pairs: BTreeMap<(usize, usize), usize> = BTreeMap::new();
+    for (index, &coverable) in coverable.indexed_iter() {
+        let owners = ownership.owners(index);
+        if owners.is_empty() && coverable {
+            orphaned.push(index);
+        }
+        if owners.len() > 1 {
+            for (i, &a) in owners.iter().enumerate() {
+                for &b in &owners[i + 1..] {
+                    *pairs.entry((a, b)).or_default() += 1;
+                }
+            }
+            overlapping.push((index, owners.to_vec()));
+        }
+    }
+    let conflicts = pairs
+        .into_iter()
+        .map(|(robots, cells)| Conflict { robots, cells })
+        .collect();
+
+    let reconciled = reconcile.then(|| {
+        let mut reconciled = ownership.clone();
+        for (index, owners) in &overlapping {
+            let support = |robot: usize| {
+                frame
+                    .neighbours(*index, Neighbourhood::Eight)
+                    .filter(|&neighbour| ownership.is_owned_by(neighbour, robot))
+                    .count()
+            };
+            let keep = owners
+                .iter()
+                .copied()
+                .max_by_key(|&robot| (support(robot), Reverse(robot)))
+                .expect("Overlapping cells have several owners");
+            for &robot in owners.iter().filter(|&&robot| robot != keep) {
+                reconciled.remove(*index, robot);
+            }
+        }
+
+        // Grow all regions at once into the orphaned cells
+        let mut queue: VecDeque<(usize, usize)> = coverable
+            .indexed_iter()
+            .map(|(index, _)| index)
+            .filter(|&index| !reconciled.owners(index).is_empty())
+            .collect();
+        while let Some(index) = queue.pop_front() {
+            let owner = reconciled.owners(index)[0];
+            for neighbour in frame.neighbours(index, Neighbourhood::Four) {
+                if coverable[neighbour] && reconciled.owners(neighbour).is_empty() {
+                    reconciled.add(neighbour, owner);
+                    queue.push_back(neighbour);
+                }
+            }
+        }
+        reconciled
+    });
+
+    Consistency {
+        overlapping,
+        orphaned,
+        conflicts,
+        reconciled,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ndarray::array;
+
+    use super::*;
+
+    #[test]
+    fn overlaps_and_gaps_are_found() {
+        let frame = GridFrame::matrix((1, 4));
+        let claims = [
+            array![[true, true, false, false]],
+            array![[false, true, false, false]],
+        ];
+        let coverable = array![[true, true, true, false]];
+
+        let consistency = check_consistency(&frame, &claims, &coverable, false);
+
+        assert_eq!(consistency.overlapping, vec![((0, 1), vec![0, 1])]);
+        assert_eq!(consistency.orphaned, vec![(0, 2)]);
+        assert_eq!(consistency.conflicts.len(), 1);
+        assert_eq!(consistency.conflicts[0].robots, (0, 1));
+        assert_eq!(consistency.conflicts[0].cells, 1);
+        assert!(consistency.reconciled.is_none());
+    }
+
+    #[test]
+    fn claims_are_reconciled() {
+        let frame = GridFrame::matrix((1, 5));
+        let claims = [
+            array![[true, true, false, false, false]],
+            array![[false, true, false, false, true]],
+        ];
+        let coverable = Array2::from_elem((1, 5), true);
+
+        let consistency = check_consistency(&frame, &claims, &coverable, true);
+
+        let reconciled = consistency.reconciled.expect("Reconciling was requested");
+        assert_eq!(reconciled.cells_of(0), vec![(0, 0), (0, 1), (0, 2)]);
+        assert_eq!(reconciled.cells_of(1), vec![(0, 3), (0, 4)]);
+    }
+
+    #[test]
+    fn cells_which_are_not_coverable_stay_unclaimed() {
+        let frame = GridFrame::matrix((1, 3));
+        let claims = [array![[true, false, false]]];
+        let coverable = array![[true, false, true]];
+
+        let consistency = check_consistency(&frame, &claims, &coverable, true);
+
+        assert_eq!(consistency.orphaned, vec![(0, 2)]);
+        let reconciled = consistency.reconciled.expect("Reconciling was requested");
+        assert_eq!(reconciled.cells_of(0), vec![(0, 0)]);
+    }
+}
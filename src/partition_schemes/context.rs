@@ -0,0 +1,282 @@
+//! This module provides the [`Context`] which is handed to every partitioning
+//! [`Scheme`] alongside the [`Map`].
+
+use std::cell::RefCell;
+
+use local_robot_map::{LocalMap, RealWorldLocation};
+use ndarray::Array2;
+
+use crate::{Map, RobotLocation};
+
+use super::{
+    Blockage, CentroidalVoronoiParameters, Convergence, DarpParameters, Deployment, Terrain,
+    TieBreak,
+};
+
+/// A partitioning scheme.
+///
+/// Unlike [`local_robot_map::Algorithm`] it also receives the request's
+/// [`Context`], which carries everything that is not part of the [`Map`].
+pub type Scheme = fn(Map, &Context) -> Map;
+
+/// Request-wide information for the partitioning schemes.
+///
+/// Robots are indexed the same way as [`robots`] iterates over them, i.e. `0`
+/// is my robot followed by the other robots in order.
+///
+/// Schemes may leave [`Diagnostics`] behind, which end up in the response.
+#[derive(Debug, Clone)]
+pub struct Context {
+    tie_break: TieBreak,
+    ranks: Vec<usize>,
+    darp: DarpParameters,
+    centroidal_voronoi: CentroidalVoronoiParameters,
+    density: Option<Array2<f64>>,
+    terrain: Option<Terrain>,
+    access: Option<Vec<Array2<bool>>>,
+    blocked: Option<Array2<Option<Blockage>>>,
+    areas: Option<Array2<Option<usize>>>,
+    diagnostics: RefCell<Diagnostics>,
+}
+
+/// Information about how a scheme arrived at its result.
+#[derive(Debug, Clone, Default)]
+pub struct Diagnostics {
+    pub convergence: Option<Convergence>,
+    pub deployment: Option<Deployment>,
+    /// How the scheme marked the border of my region, such that it can be
+    /// marked again once the region changed (see [`super::remark_border`]).
+    pub mark_border: Option<fn(Map) -> Map>,
+}
+
+impl Context {
+    /// Creates a new [`Context`].
+    ///
+    /// The robots are ranked by their id, falling back to their position for
+    /// robots without one. Since the ranks do not depend on which robot is
+    /// `me`, all robots will rank each other the same way.
+    pub fn new(ids: Vec<Option<u64>>, positions: Vec<&RealWorldLocation>) -> Self {
+        let mut order: Vec<usize> = (0..ids.len()).collect();
+        order.sort_by(|&a, &b| {
+            // robots with an id come first
+            (ids[a].is_none(), ids[a])
+                .cmp(&(ids[b].is_none(), ids[b]))
+                .then_with(|| {
+                    let (a, b) = (positions[a], positions[b]);
+                    a.x()
+                        .total_cmp(&b.x())
+                        .then_with(|| a.y().total_cmp(&b.y()))
+                        .then_with(|| a.z().total_cmp(&b.z()))
+                })
+        });
+
+        let mut ranks = vec![0; order.len()];
+        for (rank, robot) in order.into_iter().enumerate() {
+            ranks[robot] = rank;
+        }
+
+        Self {
+            tie_break: TieBreak::default(),
+            ranks,
+            darp: DarpParameters::default(),
+            centroidal_voronoi: CentroidalVoronoiParameters::default(),
+            density: None,
+            terrain: None,
+            access: None,
+            blocked: None,
+            areas: None,
+            diagnostics: RefCell::default(),
+        }
+    }
+
+    pub fn with_tie_break(mut self, tie_break: TieBreak) -> Self {
+        self.tie_break = tie_break;
+        self
+    }
+
+    pub fn with_darp(mut self, darp: DarpParameters) -> Self {
+        self.darp = darp;
+        self
+    }
+
+    pub fn with_centroidal_voronoi(mut self, parameters: CentroidalVoronoiParameters) -> Self {
+        self.centroidal_voronoi = parameters;
+        self
+    }
+
+    /// Weigh every cell by its importance. The density must have the same
+    /// dimensions as the map's cells.
+    pub fn with_density(mut self, density: Option<Array2<f64>>) -> Self {
+        self.density = density;
+        self
+    }
+
+    /// Take the cost of traversing the terrain into account. The terrain must
+    /// have the same dimensions as the map's cells.
+    pub fn with_terrain(mut self, terrain: Option<Terrain>) -> Self {
+        self.terrain = terrain;
+        self
+    }
+
+    /// Restrict the cells each robot may enter. Every robot's mask must have
+    /// the same dimensions as the map's cells.
+    pub fn with_access(mut self, access: Option<Vec<Array2<bool>>>) -> Self {
+        self.access = access;
+        self
+    }
+
+    /// Mark cells as impassable for every robot. The layer must have the same
+    /// dimensions as the map's cells.
+    pub fn with_blocked(mut self, blocked: Option<Array2<Option<Blockage>>>) -> Self {
+        self.blocked = blocked;
+        self
+    }
+
+    /// Split the map into several areas, given the area (by index) of each
+    /// cell. Cells between the areas are not part of any.
+    pub fn with_areas(mut self, areas: Option<Array2<Option<usize>>>) -> Self {
+        self.areas = areas;
+        self
+    }
+
+    pub fn tie_break(&self) -> &TieBreak {
+        &self.tie_break
+    }
+
+    pub fn darp(&self) -> &DarpParameters {
+        &self.darp
+    }
+
+    pub fn centroidal_voronoi(&self) -> &CentroidalVoronoiParameters {
+        &self.centroidal_voronoi
+    }
+
+    pub fn has_density(&self) -> bool {
+        self.density.is_some()
+    }
+
+    /// The importance of the cell at `row` and `col`. All cells are equally
+    /// important (i.e. `1`) if no density was provided.
+    pub fn density_at(&self, (row, col): (usize, usize)) -> f64 {
+        self.density
+            .as_ref()
+            .map_or(1.0, |density| density[[row, col]])
+    }
+
+    /// The cost for a robot to traverse the cell at `row` and `col`. All cells
+    /// cost `1` if no terrain was provided.
+    pub fn traversal_cost(&self, index: (usize, usize), robot: &RobotLocation) -> f64 {
+        self.terrain
+            .as_ref()
+            .map_or(1.0, |terrain| terrain.cost(index, robot))
+    }
+
+    /// Whether a robot may enter the cell at `row` and `col`. Robots may enter
+    /// all cells which are not blocked if no access restrictions were provided.
+    pub fn allows(&self, (row, col): (usize, usize), robot: usize) -> bool {
+        if self.blockage((row, col)).is_some() {
+            return false;
+        }
+        match &self.access {
+            Some(access) => access[robot][[row, col]],
+            None => true,
+        }
+    }
+
+    /// Whether any robot may enter the cell at `row` and `col`.
+    pub fn allows_anyone(&self, index: (usize, usize)) -> bool {
+        (0..self.ranks.len()).any(|robot| self.allows(index, robot))
+    }
+
+    /// Why the cell at `row` and `col` is impassable, if it is.
+    pub fn blockage(&self, (row, col): (usize, usize)) -> Option<Blockage> {
+        self.blocked
+            .as_ref()
+            .and_then(|blocked| blocked[[row, col]])
+    }
+
+    pub fn blocked(&self) -> Option<&Array2<Option<Blockage>>> {
+        self.blocked.as_ref()
+    }
+
+    pub fn has_areas(&self) -> bool {
+        self.areas.is_some()
+    }
+
+    /// The area the cell at `row` and `col` lies in. The whole map is a single
+    /// area (i.e. `0`) if no areas were provided.
+    pub fn area(&self, (row, col): (usize, usize)) -> Option<usize> {
+        match &self.areas {
+            Some(areas) => areas[[row, col]],
+            None => Some(0),
+        }
+    }
+
+    /// Record how the scheme arrived at its result.
+    pub fn record(&self, update: impl FnOnce(&mut Diagnostics)) {
+        update(&mut self.diagnostics.borrow_mut());
+    }
+
+    /// Everything recorded by the scheme so far.
+    pub fn diagnostics(&self) -> Diagnostics {
+        self.diagnostics.borrow().clone()
+    }
+
+    /// The rank of each robot, used to settle ties deterministically.
+    pub fn ranks(&self) -> &[usize] {
+        &self.ranks
+    }
+
+    /// The same context, except that `robot` may not enter any of the cells of
+    /// a map with the given dimensions, as if it was not there.
+    pub fn excluding(&self, robot: usize, (rows, cols): (usize, usize)) -> Self {
+        let mut access = self
+            .access
+            .clone()
+            .unwrap_or_else(|| vec![Array2::from_elem((rows, cols), true); self.ranks.len()]);
+        access[robot].fill(false);
+        Self {
+            access: Some(access),
+            diagnostics: RefCell::default(),
+            ..self.clone()
+        }
+    }
+
+    /// The same context as seen by another robot. See [`perspective`].
+    pub fn perspective(&self, robot: usize) -> Self {
+        Self {
+            ranks: reorder(&self.ranks, robot),
+            access: self.access.as_ref().map(|access| reorder(access, robot)),
+            diagnostics: RefCell::default(),
+            ..self.clone()
+        }
+    }
+}
+
+/// Iterate over all robots of the map, starting with my robot.
+pub fn robots(map: &Map) -> impl Iterator<Item = &RobotLocation> {
+    std::iter::once(map.my_robot()).chain(map.other_robots().iter())
+}
+
+/// The same map as seen by another robot, i.e. `robot` becomes my robot while
+/// all other robots (including the previous me) keep their relative order.
+pub fn perspective(map: &Map, robot: usize) -> Map {
+    let all: Vec<RobotLocation> = robots(map).cloned().collect();
+    let mut others = reorder(&all, robot);
+    let me = others.remove(0);
+    LocalMap::new_noexpand_nooutofmap(map.map().clone(), me, others)
+        .expect("All robots are in the map area")
+}
+
+/// Move the element at `first` to the front, keeping the order of the others.
+fn reorder<T: Clone>(items: &[T], first: usize) -> Vec<T> {
+    let rest = items
+        .iter()
+        .enumerate()
+        .filter(|(i, _)| *i != first)
+        .map(|(_, item)| item);
+    std::iter::once(&items[first])
+        .chain(rest)
+        .cloned()
+        .collect()
+}
@@ -0,0 +1,652 @@
+//! This module plans paths covering a robot's region.
+//!
+//! The paths only consist of waypoints in real-world coordinates. Transits
+//! between parts of a non-convex region are straight lines, which may hence
+//! leave the region. Only the travel to the region follows the cheapest path
+//! (see [`travel_costs`]).
+
+use std::collections::VecDeque;
+
+use local_robot_map::RealWorldLocation;
+use ndarray::Array2;
+use serde::{Deserialize, Serialize};
+
+use crate::Map;
+
+use super::{robots, travel_costs, Context, GridFrame};
+
+/// How the coverage path is planned.
+#[derive(Deserialize, Debug, Clone, Copy)]
+#[serde(tag = "planner", rename_all = "snake_case")]
+pub enum CoveragePlanner {
+    /// Parallel sweeps back and forth (i.e. a lawnmower pattern), `swath`
+    /// being the width covered by the sensor in real-world units.
+    Boustrophedon {
+        swath: f64,
+        #[serde(default)]
+        direction: SweepDirection,
+    },
+    /// Spanning Tree Coverage: a closed path around a spanning tree of the
+    /// region, coarsened to cells of twice the `tool` size, which never visits
+    /// a place twice.
+    SpanningTree { tool: f64 },
+}
+
+impl CoveragePlanner {
+    /// Whether the planner suits cells of the given size (see [`swath_fits`]).
+    /// Coarse cells of the [`CoveragePlanner::SpanningTree`] must hold the
+    /// centre of at least one cell, otherwise they fragment the region.
+    pub fn fits(&self, cell_size: (f64, f64)) -> bool {
+        match *self {
+            CoveragePlanner::Boustrophedon { swath, .. } => swath_fits(swath, cell_size),
+            CoveragePlanner::SpanningTree { tool } => {
+                tool.is_finite() && 2.0 * tool >= cell_size.0.max(cell_size.1)
+            }
+        }
+    }
+}
+
+/// Whether the `swath` is at least as wide as the cells. Narrower swaths only
+/// add lanes without covering anything more, up to an unbounded number of them.
+pub fn swath_fits(swath: f64, (size_x, size_y): (f64, f64)) -> bool {
+    swath.is_finite() && swath >= size_x.min(size_y)
+}
+
+/// The direction of the sweeps.
+#[derive(Deserialize, Debug, Default, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum SweepDirection {
+    /// The direction requiring the fewest sweeps (i.e. the region's minimum
+    /// altitude), which minimizes the number of turns.
+    #[default]
+    Auto,
+    /// Counter-clockwise from the x axis.
+    Degrees(f64),
+}
+
+/// Plan a path covering the `region`, starting near `start`.
+pub fn coverage_path(
+    planner: &CoveragePlanner,
+    region: &Array2<bool>,
+    frame: &GridFrame,
+    start: &RealWorldLocation,
+) -> Vec<RealWorldLocation> {
+    match *planner {
+        CoveragePlanner::Boustrophedon { swath, direction } => {
+            boustrophedon(region, frame, start, swath, direction)
+        }
+        CoveragePlanner::SpanningTree { tool } => spanning_tree(region, frame, start, tool),
+    }
+}
+
+/// The parameters of the completion time estimate, which assumes a
+/// [`CoveragePlanner::Boustrophedon`] path.
+#[derive(Deserialize, Debug, Clone, Copy)]
+pub struct CompletionParameters {
+    /// The width covered by the sensor in real-world units.
+    pub swath: f64,
+    #[serde(default)]
+    pub direction: SweepDirection,
+    /// The time lost per turn, in the time unit of the robots' speed.
+    #[serde(default)]
+    pub turn_penalty: f64,
+}
+
+/// How long a robot needs to cover its region.
+#[derive(Serialize, Debug, Clone, Copy)]
+pub struct Completion {
+    /// The distance to the start of the coverage path along the cheapest path,
+    /// weighted by the terrain. Infinite if the robot cannot get there.
+    pub travel: f64,
+    /// The length of the coverage path.
+    pub length: f64,
+    pub turns: usize,
+    pub time: f64,
+}
+
+/// Estimate how long the `robot` needs to get to and cover its `region`,
+/// assuming a speed of `1` if it has no [`Factors`](super::Factors). The speed
+/// must be valid (see [`Factors::is_valid`](super::Factors::is_valid)).
+pub fn estimate_completion(
+    region: &Array2<bool>,
+    map: &Map,
+    context: &Context,
+    robot: usize,
+    parameters: &CompletionParameters,
+) -> Completion {
+    let frame = GridFrame::new(map.map());
+    let location = robots(map).nth(robot).expect("The robot is in the map");
+    let start = location.location();
+    let speed = location.parameters().as_ref().map_or(1.0, |f| f.speed());
+    let path = boustrophedon(
+        region,
+        &frame,
+        start,
+        parameters.swath,
+        parameters.direction,
+    );
+    // Travel times are turned back into distances, the speed applying below
+    let travel = path.first().map_or(0.0, |first| match frame.index(first) {
+        Some(index) => travel_costs(map, context, robot)[index] * speed,
+        None => distance(start, first),
+    });
+    let length = path
+        .windows(2)
+        .map(|pair| distance(&pair[0], &pair[1]))
+        .sum();
+    let turns = path
+        .windows(3)
+        .filter(|triple| {
+            let (a, b, c) = (&triple[0], &triple[1], &triple[2]);
+            let (u, v) = (
+                (b.x() - a.x(), b.y() - a.y()),
+                (c.x() - b.x(), c.y() - b.y()),
+            );
+            let (cross, dot) = (u.0 * v.1 - u.1 * v.0, u.0 * v.0 + u.1 * v.1);
+            cross.abs() > 1e-9 * u.0.hypot(u.1) * v.0.hypot(v.1) || dot < 0.0
+        })
+        .count();
+
+    Completion {
+        travel,
+        length,
+        turns,
+        time: (travel + length) / speed + turns as f64 * parameters.turn_penalty,
+    }
+}
+
+/// The planar distance between two locations.
+fn distance(a: &RealWorldLocation, b: &RealWorldLocation) -> f64 {
+    (b.x() - a.x()).hypot(b.y() - a.y())
+}
+
+/// A straight sweep at `v` from `u.0` to `u.1`, in the rotated frame.
+#[derive(Debug, Clone, Copy)]
+struct Sweep {
+    v: f64,
+    u: (f64, f64),
+}
+
+fn boustrophedon(
+    region: &Array2<bool>,
+    frame: &GridFrame,
+    start: &RealWorldLocation,
+    swath: f64,
+    direction: SweepDirection,
+) -> Vec<RealWorldLocation> {
+    let points: Vec<(f64, f64)> = region
+        .indexed_iter()
+        .filter(|(_, inside)| **inside)
+        .map(|((row, col), _)| {
+            let location = frame.location(row, col);
+            (location.x(), location.y())
+        })
+        .collect();
+    if points.is_empty() || !swath.is_finite() || swath <= 0.0 {
+        return Vec::new();
+    }
+
+    let angle = match direction {
+        SweepDirection::Degrees(degrees) => degrees.to_radians(),
+        SweepDirection::Auto => (0..180)
+            .map(|degrees| (degrees as f64).to_radians())
+            .min_by(|&a, &b| altitude(&points, a).total_cmp(&altitude(&points, b)))
+            .expect("There are candidate directions"),
+    };
+    let (sin, cos) = angle.sin_cos();
+    let rotate = |(x, y): (f64, f64)| (x * cos + y * sin, -x * sin + y * cos);
+    let unrotate = |(u, v): (f64, f64)| (u * cos - v * sin, u * sin + v * cos);
+
+    // Every lane covers a band of one swath, the cells of which are split into
+    // sweeps wherever the region is interrupted
+    let rotated: Vec<(f64, f64)> = points.into_iter().map(rotate).collect();
+    let (v_min, v_max) = rotated
+        .iter()
+        .fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), &(_, v)| {
+            (min.min(v), max.max(v))
+        });
+    let lanes = ((v_max - v_min) / swath).floor() as usize + 1;
+    let mut bands: Vec<Vec<f64>> = vec![Vec::new(); lanes];
+    for &(u, v) in &rotated {
+        let lane = (((v - v_min) / swath).floor() as usize).min(lanes - 1);
+        bands[lane].push(u);
+    }
+
+    let (size_x, size_y) = frame.cell_size();
+    let gap = 1.5 * size_x.max(size_y);
+    let mut sweeps: Vec<Sweep> = Vec::new();
+    for (lane, mut band) in bands.into_iter().enumerate() {
+        band.sort_by(f64::total_cmp);
+        let v = (v_min + swath / 2.0 + lane as f64 * swath).min(v_max);
+        let Some(&first) = band.first() else {
+            continue;
+        };
+        let mut sweep = Sweep {
+            v,
+            u: (first, first),
+        };
+        for &u in &band[1..] {
+            if u - sweep.u.1 > gap {
+                sweeps.push(sweep);
+                sweep = Sweep { v, u: (u, u) };
+            } else {
+                sweep.u.1 = u;
+            }
+        }
+        sweeps.push(sweep);
+    }
+
+    // Greedily continue with the closest end of any remaining sweep
+    let mut position = rotate((start.x(), start.y()));
+    let mut waypoints = Vec::new();
+    while !sweeps.is_empty() {
+        let distance = |(u, v): (f64, f64)| (u - position.0).hypot(v - position.1);
+        let (index, reverse) = sweeps
+            .iter()
+            .enumerate()
+            .flat_map(|(index, sweep)| {
+                [
+                    (index, false, distance((sweep.u.0, sweep.v))),
+                    (index, true, distance((sweep.u.1, sweep.v))),
+                ]
+            })
+            .min_by(|a, b| a.2.total_cmp(&b.2))
+            .map(|(index, reverse, _)| (index, reverse))
+            .expect("There are sweeps left");
+        let Sweep { v, u: (a, b) } = sweeps.swap_remove(index);
+        let (entry, exit) = if reverse { (b, a) } else { (a, b) };
+
+        waypoints.push(unrotate((entry, v)));
+        if exit != entry {
+            waypoints.push(unrotate((exit, v)));
+        }
+        position = (exit, v);
+    }
+
+    waypoints
+        .into_iter()
+        .map(|(x, y)| RealWorldLocation::from_xyz(x, y, start.z()))
+        .collect()
+}
+
+/// The steps to the left, right, bottom and top neighbours respectively.
+const STEPS: [(isize, isize); 4] = [(-1, 0), (1, 0), (0, -1), (0, 1)];
+const LEFT: usize = 0;
+const RIGHT: usize = 1;
+const DOWN: usize = 2;
+const UP: usize = 3;
+
+/// Circumnavigate a spanning tree of the coarsened region.
+///
+/// A coarse cell is only part of the region if all the cells whose centre it
+/// holds are and it lies within the map, so cells along the region's boundary
+/// may be left out. Only the coarse cells connected to the one closest to
+/// `start` are covered.
+fn spanning_tree(
+    region: &Array2<bool>,
+    frame: &GridFrame,
+    start: &RealWorldLocation,
+    tool: f64,
+) -> Vec<RealWorldLocation> {
+    let (size_x, size_y) = frame.cell_size();
+    let centres: Vec<((f64, f64), bool)> = region
+        .indexed_iter()
+        .map(|((row, col), inside)| {
+            let location = frame.location(row, col);
+            ((location.x(), location.y()), *inside)
+        })
+        .collect();
+    let Some(origin) = centres
+        .iter()
+        .filter(|(_, inside)| *inside)
+        .map(|&((x, y), _)| (x - size_x / 2.0, y - size_y / 2.0))
+        .reduce(|a, b| (a.0.min(b.0), a.1.min(b.1)))
+    else {
+        return Vec::new();
+    };
+    if !tool.is_finite() || tool <= 0.0 {
+        return Vec::new();
+    }
+
+    // Coarse cells are indexed by [x, y], every one of them split into 2x2
+    // sub-cells of the tool's size
+    let coarse = 2.0 * tool;
+    let index = |(x, y): (f64, f64)| {
+        let (cx, cy) = ((x - origin.0) / coarse, (y - origin.1) / coarse);
+        (cx >= 0.0 && cy >= 0.0).then_some((cx as usize, cy as usize))
+    };
+    let (width, height) = centres
+        .iter()
+        .filter(|(_, inside)| *inside)
+        .filter_map(|&(centre, _)| index(centre))
+        .fold((0, 0), |(w, h), (cx, cy)| (w.max(cx + 1), h.max(cy + 1)));
+    // Becomes `None` as soon as a cell outside the region falls into it
+    let mut free = Array2::from_elem((width, height), Some(false));
+    for &(centre, inside) in &centres {
+        let Some(cell) = index(centre).filter(|&(cx, cy)| cx < width && cy < height) else {
+            continue;
+        };
+        free[cell] = match (free[cell], inside) {
+            (Some(_), true) => Some(true),
+            _ => None,
+        };
+    }
+    // Parts of a coarse cell beyond the map hold no centre, yet are not free
+    let bounds = frame.bounds();
+    let tolerance = 1e-9 * coarse;
+    let within = |(cx, cy): (usize, usize)| {
+        origin.0 + (cx + 1) as f64 * coarse <= bounds.max().x + tolerance
+            && origin.1 + (cy + 1) as f64 * coarse <= bounds.max().y + tolerance
+    };
+    let free = Array2::from_shape_fn((width, height), |cell| {
+        free[cell] == Some(true) && within(cell)
+    });
+    let neighbour = |(cx, cy): (usize, usize), (dx, dy): (isize, isize)| {
+        let (nx, ny) = (cx.checked_add_signed(dx)?, cy.checked_add_signed(dy)?);
+        (nx < width && ny < height).then_some((nx, ny))
+    };
+
+    let sub_centre = |(sx, sy): (usize, usize)| {
+        (
+            origin.0 + (sx as f64 + 0.5) * tool,
+            origin.1 + (sy as f64 + 0.5) * tool,
+        )
+    };
+    let (x, y) = (start.x(), start.y());
+    let Some(root) = free
+        .indexed_iter()
+        .filter(|(_, free)| **free)
+        .map(|(cell, _)| cell)
+        .min_by(|&a, &b| {
+            let distance = |(cx, cy): (usize, usize)| {
+                let (px, py) = sub_centre((2 * cx, 2 * cy));
+                (px + tool / 2.0 - x).hypot(py + tool / 2.0 - y)
+            };
+            distance(a).total_cmp(&distance(b))
+        })
+    else {
+        return Vec::new();
+    };
+
+    // A breadth-first spanning tree, storing the tree edges of every cell
+    let mut tree: Array2<Option<[bool; 4]>> = Array2::from_elem((width, height), None);
+    tree[root] = Some([false; 4]);
+    let mut queue = VecDeque::from([root]);
+    while let Some(cell) = queue.pop_front() {
+        for (direction, &step) in STEPS.iter().enumerate() {
+            let Some(next) = neighbour(cell, step) else {
+                continue;
+            };
+            if free[next] && tree[next].is_none() {
+                let mut edges = [false; 4];
+                edges[direction ^ 1] = true;
+                tree[next] = Some(edges);
+                if let Some(edges) = &mut tree[cell] {
+                    edges[direction] = true;
+                }
+                queue.push_back(next);
+            }
+        }
+    }
+
+    // Sub-cells are linked unless a tree edge lies between them, every one of
+    // them thus having exactly two links which form a single cycle
+    let linked = |(sx, sy): (usize, usize), direction: usize| {
+        let (dx, dy) = STEPS[direction];
+        let (nx, ny) = (sx.checked_add_signed(dx)?, sy.checked_add_signed(dy)?);
+        let (cell, other) = ((sx / 2, sy / 2), (nx / 2, ny / 2));
+        let edges = tree[cell]?;
+        if cell != other {
+            return edges[direction].then_some((nx, ny));
+        }
+        // Within a cell, the border between sub-cells runs from its centre
+        let crossed = match direction {
+            LEFT | RIGHT if sy % 2 == 0 => DOWN,
+            LEFT | RIGHT => UP,
+            _ if sx % 2 == 0 => LEFT,
+            _ => RIGHT,
+        };
+        (!edges[crossed]).then_some((nx, ny))
+    };
+    let first = (2 * root.0, 2 * root.1);
+    let mut cycle = vec![first];
+    let mut previous = None;
+    let mut current = first;
+    loop {
+        let next = (0..4)
+            .filter_map(|direction| linked(current, direction))
+            .find(|&next| Some(next) != previous)
+            .expect("Every sub-cell has two links");
+        if next == first {
+            break;
+        }
+        cycle.push(next);
+        (previous, current) = (Some(current), next);
+    }
+
+    // Start closest to the robot and only keep the corners of the path
+    let closest = (0..cycle.len())
+        .min_by(|&a, &b| {
+            let distance = |i: usize| {
+                let (px, py) = sub_centre(cycle[i]);
+                (px - x).hypot(py - y)
+            };
+            distance(a).total_cmp(&distance(b))
+        })
+        .expect("The cycle is not empty");
+    cycle.rotate_left(closest);
+    let length = cycle.len();
+    let mut waypoints: Vec<(usize, usize)> = (0..length)
+        .filter(|&i| {
+            let (before, after) = (cycle[(i + length - 1) % length], cycle[(i + 1) % length]);
+            i == 0 || (before.0 != after.0 && before.1 != after.1)
+        })
+        .map(|i| cycle[i])
+        .collect();
+    waypoints.push(cycle[0]);
+
+    waypoints
+        .into_iter()
+        .map(|sub| {
+            let (x, y) = sub_centre(sub);
+            RealWorldLocation::from_xyz(x, y, start.z())
+        })
+        .collect()
+}
+
+/// The extent of the points perpendicular to the direction `angle`.
+fn altitude(points: &[(f64, f64)], angle: f64) -> f64 {
+    let (sin, cos) = angle.sin_cos();
+    let (min, max) = points
+        .iter()
+        .map(|&(x, y)| -x * sin + y * cos)
+        .fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), v| {
+            (min.min(v), max.max(v))
+        });
+    max - min
+}
+
+#[cfg(test)]
+mod tests {
+    use local_robot_map::LocalMap;
+
+    use super::*;
+    use crate::ps::testing::{context_of, square_map};
+    use crate::ps::Factors;
+    use crate::RobotLocation;
+
+    fn xy(waypoints: &[RealWorldLocation]) -> Vec<(f64, f64)> {
+        waypoints.iter().map(|w| (w.x(), w.y())).collect()
+    }
+
+    #[test]
+    fn boustrophedon_sweeps_along_the_longest_side() {
+        let frame = GridFrame::matrix((4, 8));
+        let region = Array2::from_elem((4, 8), true);
+        let planner = CoveragePlanner::Boustrophedon {
+            swath: 2.0,
+            direction: SweepDirection::Auto,
+        };
+
+        let from_origin = coverage_path(
+            &planner,
+            &region,
+            &frame,
+            &RealWorldLocation::from_xyz(0.0, 0.0, 0.0),
+        );
+        let from_corner = coverage_path(
+            &planner,
+            &region,
+            &frame,
+            &RealWorldLocation::from_xyz(7.0, 3.0, 0.0),
+        );
+
+        // Two lanes, each centred on its band of two rows but within the region
+        assert_eq!(
+            xy(&from_origin),
+            [(0.0, 1.0), (7.0, 1.0), (7.0, 3.0), (0.0, 3.0)]
+        );
+        assert_eq!(
+            xy(&from_corner),
+            [(7.0, 3.0), (0.0, 3.0), (0.0, 1.0), (7.0, 1.0)]
+        );
+    }
+
+    #[test]
+    fn boustrophedon_splits_interrupted_lanes() {
+        let frame = GridFrame::matrix((1, 8));
+        let mut region = Array2::from_elem((1, 8), true);
+        region[[0, 3]] = false;
+        region[[0, 4]] = false;
+        let planner = CoveragePlanner::Boustrophedon {
+            swath: 1.0,
+            direction: SweepDirection::Degrees(0.0),
+        };
+
+        let path = coverage_path(
+            &planner,
+            &region,
+            &frame,
+            &RealWorldLocation::from_xyz(0.0, 0.0, 0.0),
+        );
+
+        assert_eq!(xy(&path), [(0.0, 0.0), (2.0, 0.0), (5.0, 0.0), (7.0, 0.0)]);
+    }
+
+    /// The cells passed along a path of axis-aligned unit steps, except the
+    /// last waypoint which closes it.
+    fn passed_cells(waypoints: &[(f64, f64)]) -> Vec<(usize, usize)> {
+        let mut cells = Vec::new();
+        for pair in waypoints.windows(2) {
+            let ((x0, y0), (x1, y1)) = (pair[0], pair[1]);
+            assert!(x0 == x1 || y0 == y1, "{:?} is not axis-aligned", pair);
+            let steps = (x1 - x0).abs().max((y1 - y0).abs()) as usize;
+            for step in 0..steps {
+                let t = step as f64 / steps as f64;
+                let (x, y) = (x0 + t * (x1 - x0), y0 + t * (y1 - y0));
+                cells.push((y.round() as usize, x.round() as usize));
+            }
+        }
+        cells
+    }
+
+    #[test]
+    fn spanning_tree_visits_every_cell_once() {
+        let frame = GridFrame::matrix((4, 6));
+        let mut region = Array2::from_elem((4, 6), true);
+        // An L-shape, with the upper right coarse cells left out
+        for row in 2..4 {
+            for col in 2..6 {
+                region[[row, col]] = false;
+            }
+        }
+        let planner = CoveragePlanner::SpanningTree { tool: 1.0 };
+
+        let path = xy(&coverage_path(
+            &planner,
+            &region,
+            &frame,
+            &RealWorldLocation::from_xyz(5.0, 0.0, 0.0),
+        ));
+
+        assert_eq!(path.first(), Some(&(5.0, 0.0)));
+        assert_eq!(path.first(), path.last());
+        let mut cells = passed_cells(&path);
+        cells.sort_unstable();
+        let expected: Vec<_> = region
+            .indexed_iter()
+            .filter(|(_, inside)| **inside)
+            .map(|(index, _)| index)
+            .collect();
+        assert_eq!(cells, expected);
+    }
+
+    #[test]
+    fn spanning_tree_leaves_out_partial_coarse_cells() {
+        let frame = GridFrame::matrix((3, 4));
+        let region = Array2::from_elem((3, 4), true);
+        let planner = CoveragePlanner::SpanningTree { tool: 1.0 };
+
+        let path = xy(&coverage_path(
+            &planner,
+            &region,
+            &frame,
+            &RealWorldLocation::from_xyz(0.0, 0.0, 0.0),
+        ));
+
+        // The last row only fills half of the coarse cells
+        assert_eq!(
+            path,
+            [(0.0, 0.0), (3.0, 0.0), (3.0, 1.0), (0.0, 1.0), (0.0, 0.0)]
+        );
+    }
+
+    #[test]
+    fn completion_accounts_for_the_speed() {
+        let map = square_map(6.0, &[(0, 0)]);
+        let frame = GridFrame::new(map.map());
+        let fast: Factors = serde_json::from_value(serde_json::json!({ "speed": 2.0 }))
+            .expect("The factors are valid");
+        let map = LocalMap::new_noexpand_nooutofmap(
+            map.map().clone(),
+            RobotLocation::new(frame.location(0, 0), Some(fast)),
+            vec![],
+        )
+        .expect("All robots are in the map area");
+        let region = Array2::from_shape_fn((6, 6), |(row, col)| row == 3 && col > 0);
+        let parameters = CompletionParameters {
+            swath: 2.0,
+            direction: SweepDirection::Degrees(0.0),
+            turn_penalty: 10.0,
+        };
+
+        let completion = estimate_completion(&region, &map, &context_of(&map), 0, &parameters);
+
+        // One diagonal and two straight steps to the start of a single sweep
+        let travel = 2.0 + 2f64.sqrt();
+        assert!((completion.travel - travel).abs() < 1e-9);
+        assert!((completion.length - 4.0).abs() < 1e-9);
+        assert_eq!(completion.turns, 0);
+        assert!((completion.time - (travel + 4.0) / 2.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn completion_counts_the_turns() {
+        let map = square_map(6.0, &[(5, 5)]);
+        let region = Array2::from_shape_fn((6, 6), |(row, _)| row < 4);
+        let parameters = CompletionParameters {
+            swath: 1.0,
+            direction: SweepDirection::Degrees(0.0),
+            turn_penalty: 10.0,
+        };
+
+        let completion = estimate_completion(&region, &map, &context_of(&map), 0, &parameters);
+
+        // Four sweeps of five metres, every change of lane taking two turns.
+        // The last lane is moved inside the region, being half a swath closer.
+        assert!((completion.length - 22.5).abs() < 1e-9);
+        assert_eq!(completion.turns, 6);
+        let time = completion.travel + 22.5 + 6.0 * 10.0;
+        assert!((completion.time - time).abs() < 1e-9);
+    }
+}
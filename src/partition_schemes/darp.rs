@@ -0,0 +1,379 @@
+//! *DARP* (Divide Areas based on Robots' initial Positions) partitioning.
+//!
+//! Every robot starts with a Voronoi-like region. The regions are then
+//! iteratively adjusted by scaling each robot's distances until all regions
+//! hold their fair share and are connected.
+//!
+//! Distances are measured along straight lines unless any cell is blocked, in
+//! which case they are measured along the cheapest path around the blocked
+//! cells (see [`travel_costs`]).
+//!
+//! The fair share of each robot is proportional to its [`Factors::speed`].
+//! Regions are balanced on their total importance (see
+//! [`Context::density_at`]), which is simply their number of cells if no
+//! density was provided.
+//!
+//! See: Kapoutsis, Chatzichristofis & Kosmatopoulos, *DARP: Divide Areas
+//! Algorithm for Optimal Multi-Robot Coverage Path Planning* (2017),
+//! <https://doi.org/10.1007/s10846-016-0461-x>
+//!
+//! [`Factors::speed`]: super::Factors::speed
+
+use local_robot_map::{Location, LocationType};
+use ndarray::Array2;
+use serde::{Deserialize, Serialize};
+
+use crate::Map;
+
+use super::{
+    closest_component, components, robots, travel_costs, Context, GridFrame, Neighbourhood,
+};
+
+/// The parameters of the [`darp`] scheme.
+#[derive(Deserialize, Debug, Clone, Copy)]
+#[serde(default)]
+pub struct DarpParameters {
+    /// Stop after this many iterations, even if the regions did not converge.
+    pub max_iterations: usize,
+    /// The accepted deviation of a region's weight from its fair share,
+    /// relative to the fair share.
+    pub tolerance: f64,
+    /// How strongly a region's size deviation adjusts its robot's distances.
+    pub learning_rate: f64,
+    /// How strongly a disconnected region is pulled towards its robot.
+    pub connectivity_weight: f64,
+}
+
+impl DarpParameters {
+    /// The largest number of iterations which may be requested.
+    pub const MAX_ITERATIONS: usize = 100_000;
+
+    /// Whether the parameters let the scheme terminate and keep every distance
+    /// positive: a connectivity weight in `[0, 1)`, a finite and strictly
+    /// positive learning rate, a non-negative tolerance and at most
+    /// [`Self::MAX_ITERATIONS`] iterations.
+    pub fn is_valid(&self) -> bool {
+        (0.0..1.0).contains(&self.connectivity_weight)
+            && self.learning_rate.is_finite()
+            && self.learning_rate > 0.0
+            && self.tolerance >= 0.0
+            && self.max_iterations <= Self::MAX_ITERATIONS
+    }
+}
+
+impl Default for DarpParameters {
+    fn default() -> Self {
+        Self {
+            max_iterations: 1000,
+            tolerance: 0.01,
+            learning_rate: 0.01,
+            connectivity_weight: 0.01,
+        }
+    }
+}
+
+/// How the [`darp`] scheme converged.
+#[derive(Serialize, Debug, Clone)]
+pub struct Convergence {
+    /// Whether all regions are connected and within the tolerance.
+    pub converged: bool,
+    pub iterations: usize,
+    /// The largest deviation of a region's weight from its fair share,
+    /// relative to the fair share.
+    pub imbalance: f64,
+    /// The number of robots whose region is not connected.
+    pub disconnected: usize,
+    /// The number of cells which none of the robots can reach, hence left to
+    /// nobody.
+    pub unassignable: usize,
+}
+
+/// Partition the map using DARP and record its [`Convergence`].
+pub fn darp(mut map: Map, context: &Context) -> Map {
+    let parameters = context.darp();
+    let frame = GridFrame::new(map.map());
+    let cells: Vec<(usize, usize)> = map
+        .map()
+        .cells()
+        .indexed_iter()
+        .filter(|(_, cell)| **cell == LocationType::Unexplored)
+        .map(|(index, _)| index)
+        .filter(|&index| context.allows_anyone(index))
+        .collect();
+
+    let origins: Vec<Option<(usize, usize)>> = robots(&map)
+        .map(|robot| frame.index(robot.location()))
+        .collect();
+    let speeds: Vec<f64> = robots(&map)
+        .map(|robot| robot.parameters().as_ref().map_or(1.0, |f| f.speed()))
+        .collect();
+    // Travel times are turned back into distances, since the shares already
+    // account for the speeds
+    let paths: Option<Vec<Array2<f64>>> = context.blocked().is_some().then(|| {
+        (0..origins.len())
+            .map(|robot| travel_costs(&map, context, robot))
+            .collect()
+    });
+    let distances: Vec<Vec<f64>> = robots(&map)
+        .enumerate()
+        .map(|(index, robot)| {
+            cells
+                .iter()
+                .map(|&(row, col)| match &paths {
+                    _ if !context.allows((row, col), index) => f64::INFINITY,
+                    Some(paths) => paths[index][[row, col]] * speeds[index],
+                    None => robot.location().distance(&frame.location(row, col)),
+                })
+                .collect()
+        })
+        .collect();
+    // Cells which no robot can reach are left to nobody
+    let reachable: Vec<bool> = (0..cells.len())
+        .map(|cell| {
+            distances
+                .iter()
+                .any(|distances| distances[cell].is_finite())
+        })
+        .collect();
+    let unassignable = reachable.iter().filter(|&&reachable| !reachable).count();
+    let cells: Vec<(usize, usize)> = cells
+        .into_iter()
+        .zip(&reachable)
+        .filter_map(|(cell, &reachable)| reachable.then_some(cell))
+        .collect();
+    if cells.is_empty() {
+        return map;
+    }
+    let distances: Vec<Vec<f64>> = distances
+        .into_iter()
+        .map(|distances| {
+            distances
+                .into_iter()
+                .zip(&reachable)
+                .filter_map(|(distance, &reachable)| reachable.then_some(distance))
+                .collect()
+        })
+        .collect();
+    let mut weights: Vec<f64> = cells
+        .iter()
+        .map(|&index| context.density_at(index))
+        .collect();
+    if weights.iter().sum::<f64>() <= 0.0 {
+        // nothing is important, hence everything is equally important
+        weights.fill(1.0);
+    }
+    let total_speed: f64 = speeds.iter().sum();
+    let total_weight: f64 = weights.iter().sum();
+    let shares: Vec<f64> = speeds
+        .iter()
+        .map(|speed| total_weight * speed / total_speed)
+        .collect();
+
+    let robot_count = origins.len();
+    let mut scales = vec![1.0; robot_count];
+    let mut connectivity = vec![vec![1.0; cells.len()]; robot_count];
+    let mut owners = vec![0; cells.len()];
+    let mut convergence = Convergence {
+        converged: false,
+        iterations: 0,
+        imbalance: f64::INFINITY,
+        disconnected: 0,
+        unassignable,
+    };
+
+    while convergence.iterations < parameters.max_iterations {
+        convergence.iterations += 1;
+
+        for (cell, owner) in owners.iter_mut().enumerate() {
+            *owner = (0..robot_count)
+                .min_by(|&a, &b| {
+                    let cost_a = scales[a] * connectivity[a][cell] * distances[a][cell];
+                    let cost_b = scales[b] * connectivity[b][cell] * distances[b][cell];
+                    cost_a
+                        .total_cmp(&cost_b)
+                        .then_with(|| context.ranks()[a].cmp(&context.ranks()[b]))
+                })
+                .expect("There is at least one robot");
+        }
+
+        let mut sizes = vec![0.0; robot_count];
+        for (&owner, weight) in owners.iter().zip(&weights) {
+            sizes[owner] += weight;
+        }
+        convergence.imbalance = (0..robot_count)
+            .map(|robot| (sizes[robot] - shares[robot]).abs() / shares[robot])
+            .fold(0.0, f64::max);
+
+        convergence.disconnected = 0;
+        for (robot, pull) in connectivity.iter_mut().enumerate() {
+            let region: Vec<(usize, usize)> = cells
+                .iter()
+                .zip(&owners)
+                .filter(|(_, &owner)| owner == robot)
+                .map(|(&cell, _)| cell)
+                .collect();
+            let mut parts = components(&frame, &region, Neighbourhood::Four);
+            if parts.len() <= 1 {
+                pull.fill(1.0);
+                continue;
+            }
+            convergence.disconnected += 1;
+
+            let main = parts.remove(match origins[robot] {
+                Some(origin) => closest_component(&parts, origin),
+                None => 0,
+            });
+            let rest: Vec<(usize, usize)> = parts.into_iter().flatten().collect();
+            *pull = pull_towards(&frame, &cells, &main, &rest, parameters.connectivity_weight);
+        }
+
+        if convergence.disconnected == 0 && convergence.imbalance <= parameters.tolerance {
+            convergence.converged = true;
+            break;
+        }
+
+        for (robot, scale) in scales.iter_mut().enumerate() {
+            let deviation = (sizes[robot] - shares[robot]) / shares[robot];
+            *scale = (*scale + parameters.learning_rate * deviation).max(f64::EPSILON);
+        }
+    }
+
+    for (&(row, col), _) in cells.iter().zip(&owners).filter(|(_, &owner)| owner == 0) {
+        map.map_mut()
+            .set_location(&frame.location(row, col), LocationType::Assigned)
+            .expect("All locations are in the map");
+    }
+
+    context.record(|diagnostics| diagnostics.convergence = Some(convergence));
+    map
+}
+
+/// Factors making cells close to the robot's `main` component cheaper and
+/// those close to the `rest` of its region more expensive, normalized to
+/// `1 ± weight`.
+fn pull_towards(
+    frame: &GridFrame,
+    cells: &[(usize, usize)],
+    main: &[(usize, usize)],
+    rest: &[(usize, usize)],
+    weight: f64,
+) -> Vec<f64> {
+    let to_main = frame.steps_from(main, Neighbourhood::Four);
+    let to_rest = frame.steps_from(rest, Neighbourhood::Four);
+    let raw: Vec<f64> = cells
+        .iter()
+        .map(|&(row, col)| to_main[[row, col]] as f64 - to_rest[[row, col]] as f64)
+        .collect();
+
+    let min = raw.iter().copied().fold(f64::INFINITY, f64::min);
+    let max = raw.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+    if max - min <= f64::EPSILON {
+        return vec![1.0; cells.len()];
+    }
+
+    raw.into_iter()
+        .map(|value| 1.0 + weight * (2.0 * (value - min) / (max - min) - 1.0))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ps::testing::{context_of, square_map};
+    use crate::ps::Blockage;
+
+    #[test]
+    fn regions_are_balanced() {
+        // The Voronoi regions would hold a third and two thirds of the cells
+        let map = square_map(6.0, &[(0, 0), (0, 3)]);
+        let context = context_of(&map);
+        let unexplored = map
+            .map()
+            .cells()
+            .iter()
+            .filter(|&cell| *cell == LocationType::Unexplored)
+            .count();
+
+        let map = darp(map, &context);
+
+        let assigned = map
+            .map()
+            .cells()
+            .iter()
+            .filter(|&cell| *cell == LocationType::Assigned)
+            .count();
+        assert_eq!(assigned, unexplored / 2);
+        let convergence = context.diagnostics().convergence.expect("DARP records it");
+        assert!(convergence.converged);
+        assert_eq!(convergence.disconnected, 0);
+    }
+
+    #[test]
+    fn unreachable_cells_are_left_to_nobody() {
+        // A wall along the third column cuts off the last two columns
+        let map = square_map(6.0, &[(0, 0), (5, 0)]);
+        let wall =
+            Array2::from_shape_fn((6, 6), |(_, col)| (col == 3).then_some(Blockage::Obstacle));
+        let context = context_of(&map).with_blocked(Some(wall));
+
+        let map = darp(map, &context);
+
+        let cells = map.map().cells();
+        assert!(cells
+            .indexed_iter()
+            .filter(|((_, col), _)| *col > 3)
+            .all(|(_, cell)| *cell == LocationType::Unexplored));
+        let convergence = context.diagnostics().convergence.expect("DARP records it");
+        assert_eq!(convergence.unassignable, 12);
+    }
+
+    #[test]
+    fn parameters_are_validated() {
+        let valid = DarpParameters::default();
+        assert!(valid.is_valid());
+
+        let invalid = [
+            DarpParameters {
+                connectivity_weight: 1.0,
+                ..valid
+            },
+            DarpParameters {
+                connectivity_weight: -0.1,
+                ..valid
+            },
+            DarpParameters {
+                learning_rate: 0.0,
+                ..valid
+            },
+            DarpParameters {
+                learning_rate: f64::INFINITY,
+                ..valid
+            },
+            DarpParameters {
+                tolerance: -0.1,
+                ..valid
+            },
+            DarpParameters {
+                tolerance: f64::NAN,
+                ..valid
+            },
+            DarpParameters {
+                max_iterations: usize::MAX,
+                ..valid
+            },
+        ];
+        assert!(invalid.iter().all(|parameters| !parameters.is_valid()));
+    }
+
+    #[test]
+    fn pull_favours_the_main_component() {
+        let frame = GridFrame::matrix((1, 5));
+        let cells: Vec<(usize, usize)> = (0..5).map(|col| (0, col)).collect();
+
+        let pull = pull_towards(&frame, &cells, &[(0, 0)], &[(0, 4)], 0.1);
+
+        assert!((pull[0] - 0.9).abs() < 1e-9);
+        assert!((pull[2] - 1.0).abs() < 1e-9);
+        assert!((pull[4] - 1.1).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,236 @@
+//! This module provides the classic exploration frontiers, i.e. unexplored
+//! cells adjacent to explored free space.
+//!
+//! Not to be confused with [`LocationType::Frontier`], which marks the border
+//! of the assigned region. The frontiers are hence kept as a separate layer and
+//! clustered into segments, which are then assigned to the robots.
+
+use local_robot_map::{LocationType, RealWorldLocation};
+use ndarray::Array2;
+use serde::Deserialize;
+
+use crate::Map;
+
+use super::{components, travel_costs, Context, GridFrame, Neighbourhood, Ownership};
+
+/// The parameters of the frontier detection.
+#[derive(Deserialize, Debug, Clone, Copy)]
+#[serde(default)]
+pub struct FrontierParameters {
+    pub neighbourhood: Neighbourhood,
+    /// Segments with fewer cells are dropped as noise.
+    pub min_size: usize,
+    pub assignment: FrontierAssignment,
+}
+
+impl Default for FrontierParameters {
+    fn default() -> Self {
+        Self {
+            neighbourhood: Neighbourhood::Eight,
+            min_size: 1,
+            assignment: FrontierAssignment::default(),
+        }
+    }
+}
+
+/// How frontier segments are assigned to the robots.
+#[derive(Deserialize, Debug, Default, Clone, Copy, PartialEq)]
+#[serde(tag = "rule", rename_all = "snake_case")]
+pub enum FrontierAssignment {
+    /// Each segment goes to the robot owning most of its cells.
+    #[default]
+    Region,
+    /// Each segment goes to the robot with the highest utility, being the
+    /// segment's size minus `cost_weight` times the travel cost (see
+    /// [`travel_costs`]) to its centroid.
+    Utility { cost_weight: f64 },
+}
+
+/// A connected segment of frontier cells.
+#[derive(Debug, Clone)]
+pub struct FrontierSegment {
+    pub cells: Vec<(usize, usize)>,
+    /// The mean location of the cells.
+    pub centroid: RealWorldLocation,
+    /// The robot the segment is assigned to, if any can reach it.
+    pub robot: Option<usize>,
+}
+
+/// Find the frontier segments of the (not yet partitioned) `map`.
+///
+/// Explored free space consists of [`LocationType::Explored`] cells which are
+/// not blocked (see [`Context::blockage`]).
+pub fn frontiers(
+    map: &Map,
+    context: &Context,
+    parameters: &FrontierParameters,
+) -> Vec<FrontierSegment> {
+    let frame = GridFrame::new(map.map());
+    let cells = map.map().cells();
+    let explored = Array2::from_shape_fn(cells.dim(), |index| {
+        cells[index] == LocationType::Explored && context.blockage(index).is_none()
+    });
+
+    let frontier: Vec<(usize, usize)> = cells
+        .indexed_iter()
+        .filter(|&(index, cell)| {
+            *cell == LocationType::Unexplored
+                && context.blockage(index).is_none()
+                && frame
+                    .neighbours(index, parameters.neighbourhood)
+                    .any(|(row, col)| explored[[row, col]])
+        })
+        .map(|(index, _)| index)
+        .collect();
+
+    components(&frame, &frontier, parameters.neighbourhood)
+        .into_iter()
+        .filter(|cells| cells.len() >= parameters.min_size.max(1))
+        .map(|cells| {
+            let (x, y) = cells.iter().fold((0.0, 0.0), |(x, y), &(row, col)| {
+                let location = frame.location(row, col);
+                (x + location.x(), y + location.y())
+            });
+            let count = cells.len() as f64;
+            FrontierSegment {
+                centroid: RealWorldLocation::from_xyz(x / count, y / count, 0.0),
+                cells,
+                robot: None,
+            }
+        })
+        .collect()
+}
+
+/// Assign the segments to the robots according to the
+/// [`FrontierParameters::assignment`].
+///
+/// The `ownership` is required for [`FrontierAssignment::Region`], segments
+/// are left unassigned otherwise.
+pub fn assign_frontiers(
+    segments: &mut [FrontierSegment],
+    map: &Map,
+    context: &Context,
+    ownership: Option<&Ownership>,
+    parameters: &FrontierParameters,
+) {
+    let robots = context.ranks().len();
+    // Higher is better, ties being settled by the robots' ranks
+    let best = |scores: Vec<f64>| -> Option<usize> {
+        (0..robots)
+            .filter(|&robot| scores[robot].is_finite())
+            .max_by(|&a, &b| {
+                scores[a]
+                    .total_cmp(&scores[b])
+                    .then_with(|| context.ranks()[b].cmp(&context.ranks()[a]))
+            })
+    };
+
+    match parameters.assignment {
+        FrontierAssignment::Region => {
+            let Some(ownership) = ownership else {
+                return;
+            };
+            for segment in segments.iter_mut() {
+                let mut counts = vec![f64::NEG_INFINITY; robots];
+                for &cell in &segment.cells {
+                    for &robot in ownership.owners(cell) {
+                        counts[robot] = counts[robot].max(0.0) + 1.0;
+                    }
+                }
+                segment.robot = best(counts);
+            }
+        }
+        FrontierAssignment::Utility { cost_weight } => {
+            let frame = GridFrame::new(map.map());
+            let costs: Vec<Array2<f64>> = (0..robots)
+                .map(|robot| travel_costs(map, context, robot))
+                .collect();
+            for segment in segments.iter_mut() {
+                let (row, col) = closest_cell(&frame, segment);
+                let utilities = costs
+                    .iter()
+                    .map(|costs| segment.cells.len() as f64 - cost_weight * costs[[row, col]])
+                    .collect();
+                segment.robot = best(utilities);
+            }
+        }
+    }
+}
+
+/// The segment's cell closest to its centroid, which may lie off the segment.
+pub(crate) fn closest_cell(frame: &GridFrame, segment: &FrontierSegment) -> (usize, usize) {
+    *segment
+        .cells
+        .iter()
+        .min_by(|&&(row_a, col_a), &&(row_b, col_b)| {
+            let (a, b) = (frame.location(row_a, col_a), frame.location(row_b, col_b));
+            let distance = |l: &RealWorldLocation| {
+                (l.x() - segment.centroid.x()).hypot(l.y() - segment.centroid.y())
+            };
+            distance(&a).total_cmp(&distance(&b))
+        })
+        .expect("Segments are never empty")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ps::testing::{context_of, ownership_of, square_map};
+
+    /// A map whose two left columns and bottom right corner are explored, with
+    /// me next to the frontier and the other robot in the corner.
+    fn explored_map() -> Map {
+        let mut map = square_map(6.0, &[(2, 3), (5, 5)]);
+        let frame = GridFrame::new(map.map());
+        for (row, col) in (0..6).flat_map(|row| [(row, 0), (row, 1)]).chain([(5, 5)]) {
+            map.map_mut()
+                .set_location(&frame.location(row, col), LocationType::Explored)
+                .expect("All locations are in the map");
+        }
+        map
+    }
+
+    #[test]
+    fn small_segments_are_dropped() {
+        let map = explored_map();
+        let frame = GridFrame::new(map.map());
+        let parameters = FrontierParameters {
+            neighbourhood: Neighbourhood::Four,
+            min_size: 2,
+            ..FrontierParameters::default()
+        };
+
+        let segments = frontiers(&map, &context_of(&map), &parameters);
+
+        // The corner's frontier cells only touch diagonally
+        assert_eq!(segments.len(), 1);
+        assert_eq!(
+            segments[0].cells,
+            (0..6).map(|row| (row, 2)).collect::<Vec<_>>()
+        );
+        let (top, bottom) = (frame.location(0, 2), frame.location(5, 2));
+        assert_eq!(segments[0].centroid.x(), top.x());
+        assert!((segments[0].centroid.y() - (top.y() + bottom.y()) / 2.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn segments_go_to_their_owner_or_the_closest_robot() {
+        let map = explored_map();
+        let context = context_of(&map);
+        let mut parameters = FrontierParameters {
+            neighbourhood: Neighbourhood::Four,
+            min_size: 2,
+            ..FrontierParameters::default()
+        };
+        let mut segments = frontiers(&map, &context, &parameters);
+        // The other robot owns most of the segment
+        let ownership = ownership_of((6, 6), |(row, _)| Some(usize::from(row < 4)));
+
+        assign_frontiers(&mut segments, &map, &context, Some(&ownership), &parameters);
+        assert_eq!(segments[0].robot, Some(1));
+
+        parameters.assignment = FrontierAssignment::Utility { cost_weight: 1.0 };
+        assign_frontiers(&mut segments, &map, &context, None, &parameters);
+        assert_eq!(segments[0].robot, Some(0));
+    }
+}
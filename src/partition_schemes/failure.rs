@@ -0,0 +1,128 @@
+//! This module hands the region of failed robots over to their neighbours.
+//!
+//! The partition is computed as if the failed robots were still alive, so the
+//! regions of all other robots stay untouched and these need not re-plan.
+
+use std::collections::VecDeque;
+
+use local_robot_map::LocationType;
+use ndarray::Array2;
+use serde::Serialize;
+
+use crate::Map;
+
+use super::{Context, GridFrame, Neighbourhood, Ownership};
+
+/// The number of cells handed over to or from a robot.
+#[derive(Serialize, Debug, Clone, Copy)]
+pub struct Handover {
+    pub robot: usize,
+    pub cells: usize,
+}
+
+/// Redistribute the unexplored cells of the `failed` robots among the robots
+/// whose regions border theirs.
+///
+/// Each cell goes to the closest of these regions, growing all of them at once
+/// across the cells which the respective robot may enter. Returns the robots
+/// which gained cells along with the cells nobody could take over.
+pub fn redistribute(
+    ownership: &mut Ownership,
+    map: &Map,
+    context: &Context,
+    failed: &[usize],
+) -> (Vec<Handover>, Vec<(usize, usize)>) {
+    let frame = GridFrame::new(map.map());
+    let cells = map.map().cells();
+    let mut vacant = Array2::from_elem((frame.rows(), frame.cols()), false);
+    for &robot in failed {
+        for index in ownership.cells_of(robot) {
+            ownership.remove(index, robot);
+            if ownership.owners(index).is_empty() && cells[index] != LocationType::Explored {
+                vacant[index] = true;
+            }
+        }
+    }
+
+    // The cells of the neighbouring robots which border the vacant ones
+    let mut queue: VecDeque<((usize, usize), usize)> = VecDeque::new();
+    for (index, _) in vacant.indexed_iter().filter(|(_, vacant)| **vacant) {
+        for neighbour in frame.neighbours(index, Neighbourhood::Four) {
+            for &robot in ownership.owners(neighbour) {
+                queue.push_back((neighbour, robot));
+            }
+        }
+    }
+
+    let mut gained = vec![0; context.ranks().len()];
+    while let Some((index, robot)) = queue.pop_front() {
+        for neighbour in frame.neighbours(index, Neighbourhood::Four) {
+            if vacant[neighbour] && context.allows(neighbour, robot) {
+                vacant[neighbour] = false;
+                ownership.add(neighbour, robot);
+                gained[robot] += 1;
+                queue.push_back((neighbour, robot));
+            }
+        }
+    }
+
+    let handovers = gained
+        .into_iter()
+        .enumerate()
+        .filter(|&(_, cells)| cells > 0)
+        .map(|(robot, cells)| Handover { robot, cells })
+        .collect();
+    let stranded = vacant
+        .indexed_iter()
+        .filter(|(_, vacant)| **vacant)
+        .map(|(index, _)| index)
+        .collect();
+    (handovers, stranded)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ps::testing::{context_of, ownership_of, square_map};
+
+    /// Three robots owning a third of the columns each.
+    fn strips() -> (Map, Ownership) {
+        let map = square_map(6.0, &[(0, 0), (0, 2), (0, 4)]);
+        let frame = GridFrame::new(map.map());
+        let cols = frame.cols();
+        let ownership = ownership_of((frame.rows(), cols), |(_, col)| Some(col * 3 / cols));
+        (map, ownership)
+    }
+
+    #[test]
+    fn neighbours_take_over_evenly() {
+        let (map, mut ownership) = strips();
+        let context = context_of(&map);
+        let vacant = ownership.cells_of(1).len();
+
+        let (gained, stranded) = redistribute(&mut ownership, &map, &context, &[1]);
+
+        assert!(stranded.is_empty());
+        assert!(ownership.cells_of(1).is_empty());
+        let gained: Vec<(usize, usize)> = gained.iter().map(|h| (h.robot, h.cells)).collect();
+        assert_eq!(gained, vec![(0, vacant / 2), (2, vacant / 2)]);
+    }
+
+    #[test]
+    fn cells_nobody_may_enter_are_stranded() {
+        let (map, mut ownership) = strips();
+        let frame = GridFrame::new(map.map());
+        let vacant = ownership.cells_of(1);
+        let mut access = vec![Array2::from_elem((frame.rows(), frame.cols()), true); 3];
+        for &index in &vacant {
+            access[0][index] = false;
+            access[2][index] = false;
+        }
+        let context = context_of(&map).with_access(Some(access));
+
+        let (gained, stranded) = redistribute(&mut ownership, &map, &context, &[1]);
+
+        assert!(gained.is_empty());
+        assert_eq!(stranded, vacant);
+    }
+}
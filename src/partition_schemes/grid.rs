@@ -0,0 +1,230 @@
+//! This module provides the [`GridFrame`] which converts between cell indices
+//! (`row`, `col`) and real-world locations.
+
+use std::collections::VecDeque;
+use std::ops::Range;
+
+use geo::{BoundingRect, Intersects};
+use local_robot_map::{Cell, CellMap, Coords, Location, LocationType, RealWorldLocation};
+use ndarray::Array2;
+
+/// The relation between the cells of a [`CellMap`] and the real world.
+///
+/// The frame is derived from [`Cell::from_internal`], hence it follows the same
+/// conventions as the rest of the map.
+#[derive(Debug, Clone)]
+pub struct GridFrame {
+    origin: RealWorldLocation,
+    step_x: f64,
+    step_y: f64,
+    rows: usize,
+    cols: usize,
+}
+
+impl GridFrame {
+    pub fn new(map: &CellMap) -> Self {
+        let location = |col: f64, row: f64| -> RealWorldLocation {
+            Cell::from_internal(
+                Coords::new(col, row, 0.0),
+                *map.offset(),
+                *map.resolution(),
+                &LocationType::Unexplored,
+            )
+            .expect("Internal coordinates can always be converted")
+            .location()
+            .clone()
+        };
+        let origin = location(0.0, 0.0);
+        let step = location(1.0, 1.0);
+        let (rows, cols) = map.cells().dim();
+
+        Self {
+            step_x: step.x() - origin.x(),
+            step_y: step.y() - origin.y(),
+            origin,
+            rows,
+            cols,
+        }
+    }
+
+    /// The frame of a bare matrix of `rows` and `cols`, the location of every
+    /// cell being its matrix coordinates (`col`, `row`).
+    pub fn matrix((rows, cols): (usize, usize)) -> Self {
+        Self {
+            origin: RealWorldLocation::from_xyz(0.0, 0.0, 0.0),
+            step_x: 1.0,
+            step_y: 1.0,
+            rows,
+            cols,
+        }
+    }
+
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    pub fn cols(&self) -> usize {
+        self.cols
+    }
+
+    /// The size of a cell along the x and y axis in real-world units.
+    pub fn cell_size(&self) -> (f64, f64) {
+        (self.step_x.abs(), self.step_y.abs())
+    }
+
+    /// The real-world location of the cell at `row` and `col`.
+    pub fn location(&self, row: usize, col: usize) -> RealWorldLocation {
+        RealWorldLocation::from_xyz(
+            self.origin.x() + col as f64 * self.step_x,
+            self.origin.y() + row as f64 * self.step_y,
+            self.origin.z(),
+        )
+    }
+
+    /// The real-world location of the corner which the cell at `row` and `col`
+    /// shares with the cells at `row - 1` and `col - 1`. Corners hence range up
+    /// to `rows` and `cols` inclusively.
+    pub fn corner(&self, row: usize, col: usize) -> RealWorldLocation {
+        RealWorldLocation::from_xyz(
+            self.origin.x() + (col as f64 - 0.5) * self.step_x,
+            self.origin.y() + (row as f64 - 0.5) * self.step_y,
+            self.origin.z(),
+        )
+    }
+
+    /// The rectangle spanned by all cells of the map.
+    pub fn bounds(&self) -> geo::Rect<f64> {
+        let (min, max) = (self.corner(0, 0), self.corner(self.rows, self.cols));
+        geo::Rect::new((min.x(), min.y()), (max.x(), max.y()))
+    }
+
+    /// The cell containing a real-world location, if it is inside the map.
+    pub fn index(&self, location: &RealWorldLocation) -> Option<(usize, usize)> {
+        let col = ((location.x() - self.origin.x()) / self.step_x).round();
+        let row = ((location.y() - self.origin.y()) / self.step_y).round();
+        if col < 0.0 || row < 0.0 || col >= self.cols as f64 || row >= self.rows as f64 {
+            return None;
+        }
+        Some((row as usize, col as usize))
+    }
+
+    /// All cells whose location lies inside (or on the border of) a polygon,
+    /// in row-major order.
+    pub fn covered_by(&self, polygon: &geo::Polygon<f64>) -> Vec<(usize, usize)> {
+        // Only the cells within the polygon's bounding box are candidates
+        let Some(rect) = polygon.bounding_rect() else {
+            return Vec::new();
+        };
+        let (min, max) = (rect.min(), rect.max());
+        let rows = span((min.y, max.y), self.origin.y(), self.step_y, self.rows);
+        let cols = span((min.x, max.x), self.origin.x(), self.step_x, self.cols);
+
+        rows.flat_map(|row| cols.clone().map(move |col| (row, col)))
+            .filter(|&(row, col)| {
+                let location = self.location(row, col);
+                polygon.intersects(&geo::Point::new(location.x(), location.y()))
+            })
+            .collect()
+    }
+
+    /// All cells overlapping a rectangle, in row-major order. Cells merely
+    /// touching its border are left out.
+    pub fn overlapping(&self, rect: &geo::Rect<f64>) -> Vec<(usize, usize)> {
+        let (min, max) = (rect.min(), rect.max());
+        let rows = overlap((min.y, max.y), self.origin.y(), self.step_y, self.rows);
+        let cols = overlap((min.x, max.x), self.origin.x(), self.step_x, self.cols);
+
+        rows.flat_map(|row| cols.clone().map(move |col| (row, col)))
+            .collect()
+    }
+
+    /// The neighbouring cells of the cell at `row` and `col`.
+    pub fn neighbours(
+        &self,
+        (row, col): (usize, usize),
+        neighbourhood: Neighbourhood,
+    ) -> impl Iterator<Item = (usize, usize)> + '_ {
+        neighbourhood
+            .offsets()
+            .iter()
+            .filter_map(move |(d_row, d_col)| {
+                let row = row.checked_add_signed(*d_row)?;
+                let col = col.checked_add_signed(*d_col)?;
+                (row < self.rows && col < self.cols).then_some((row, col))
+            })
+    }
+
+    /// The number of steps from the closest source to every cell of the grid,
+    /// moving freely across all cells.
+    pub fn steps_from(
+        &self,
+        sources: &[(usize, usize)],
+        neighbourhood: Neighbourhood,
+    ) -> Array2<usize> {
+        let mut steps = Array2::from_elem((self.rows, self.cols), usize::MAX);
+        let mut queue = VecDeque::new();
+        for &(row, col) in sources {
+            steps[[row, col]] = 0;
+            queue.push_back((row, col));
+        }
+
+        while let Some(cell) = queue.pop_front() {
+            let next = steps[[cell.0, cell.1]] + 1;
+            for (row, col) in self.neighbours(cell, neighbourhood) {
+                if steps[[row, col]] > next {
+                    steps[[row, col]] = next;
+                    queue.push_back((row, col));
+                }
+            }
+        }
+
+        steps
+    }
+}
+
+/// The indices along one axis of the cells between the coordinates `a` and `b`
+/// (and possibly a few more).
+fn span((a, b): (f64, f64), origin: f64, step: f64, len: usize) -> Range<usize> {
+    let (a, b) = ((a - origin) / step, (b - origin) / step);
+    let first = a.min(b).floor().max(0.0) as usize;
+    let last = (a.max(b).ceil().max(-1.0) + 1.0) as usize;
+    first.min(len)..last.min(len)
+}
+
+/// The indices along one axis of the cells which overlap the interval between
+/// the coordinates `a` and `b`.
+fn overlap((a, b): (f64, f64), origin: f64, step: f64, len: usize) -> Range<usize> {
+    let (a, b) = ((a - origin) / step, (b - origin) / step);
+    let first = ((a.min(b) - 0.5).floor() + 1.0).max(0.0) as usize;
+    let last = (a.max(b) + 0.5).ceil().max(0.0) as usize;
+    first.min(len)..last.min(len)
+}
+
+/// Which cells are considered to be adjacent to each other.
+#[derive(serde::Deserialize, Debug, Default, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum Neighbourhood {
+    /// Cells sharing an edge.
+    #[default]
+    Four,
+    /// Cells sharing an edge or a corner.
+    Eight,
+}
+
+impl Neighbourhood {
+    fn offsets(&self) -> &'static [(isize, isize)] {
+        match self {
+            Neighbourhood::Four => &[(-1, 0), (0, -1), (0, 1), (1, 0)],
+            Neighbourhood::Eight => &[
+                (-1, -1),
+                (-1, 0),
+                (-1, 1),
+                (0, -1),
+                (0, 1),
+                (1, -1),
+                (1, 0),
+                (1, 1),
+            ],
+        }
+    }
+}
@@ -0,0 +1,160 @@
+//! This module lets a robot join without reshuffling everyone's region.
+//!
+//! The partition is computed as if the newcomer was not there. The newcomer
+//! then takes the cells closest to it, thus only from nearby robots, while the
+//! regions elsewhere stay untouched.
+
+use std::collections::VecDeque;
+
+use ndarray::Array2;
+use serde::Deserialize;
+
+use crate::Map;
+
+use super::{robots, Context, GridFrame, Handover, Neighbourhood, Ownership};
+
+/// How a robot joins.
+#[derive(Deserialize, Debug, Clone, Copy)]
+pub struct JoinParameters {
+    /// Index of the newcomer, `0` being `me` followed by `others` in order.
+    pub robot: usize,
+    /// Which part of its fair share the newcomer takes, trading balance off
+    /// against disruption: `0` changes nothing while `1` takes a fair share.
+    #[serde(default = "default_balance")]
+    pub balance: f64,
+}
+
+fn default_balance() -> f64 {
+    1.0
+}
+
+/// Let the newcomer take cells from the robots in `ownership`, which must not
+/// hold any cells of the newcomer yet.
+///
+/// The newcomer takes the owned cells in the order it reaches them from its
+/// position, moving across the cells it may enter, until it holds `balance`
+/// times the owned cells divided by the number of robots. Returns how many
+/// cells the newcomer took along with how many each robot lost, which differ
+/// for cells shared by several robots.
+///
+/// Taking cells may split the regions of the other robots, hence connectivity
+/// is to be enforced afterwards if required.
+pub fn join(
+    ownership: &mut Ownership,
+    map: &Map,
+    context: &Context,
+    parameters: &JoinParameters,
+) -> (usize, Vec<Handover>) {
+    let frame = GridFrame::new(map.map());
+    let robot = parameters.robot;
+    let owned = ownership.count_owned();
+    let share = parameters.balance.clamp(0.0, 1.0) * owned as f64 / robots(map).count() as f64;
+    let quota = share.round() as usize;
+
+    let mut lost = vec![0; context.ranks().len()];
+    let mut taken = 0;
+    let mut visited = Array2::from_elem((frame.rows(), frame.cols()), false);
+    let start = robots(map)
+        .nth(robot)
+        .and_then(|newcomer| frame.index(newcomer.location()))
+        .filter(|&index| context.allows(index, robot));
+    let mut queue: VecDeque<(usize, usize)> = start.into_iter().collect();
+    if let Some(start) = start {
+        visited[start] = true;
+    }
+    while let Some(index) = queue.pop_front() {
+        if taken == quota {
+            break;
+        }
+        let owners = ownership.owners(index).to_vec();
+        if !owners.is_empty() {
+            for owner in owners {
+                ownership.remove(index, owner);
+                lost[owner] += 1;
+            }
+            ownership.add(index, robot);
+            taken += 1;
+        }
+        for neighbour in frame.neighbours(index, Neighbourhood::Four) {
+            if !visited[neighbour] && context.allows(neighbour, robot) {
+                visited[neighbour] = true;
+                queue.push_back(neighbour);
+            }
+        }
+    }
+
+    let lost = lost
+        .into_iter()
+        .enumerate()
+        .filter(|&(_, cells)| cells > 0)
+        .map(|(robot, cells)| Handover { robot, cells })
+        .collect();
+    (taken, lost)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ps::testing::{context_of, ownership_of, square_map};
+
+    /// Robots `0` and `1` owning a half each, while robot `2` joins in the
+    /// middle of the map.
+    fn halves() -> (Map, Ownership) {
+        let map = square_map(6.0, &[(0, 0), (0, 5), (3, 3)]);
+        let frame = GridFrame::new(map.map());
+        let cols = frame.cols();
+        let ownership = ownership_of((frame.rows(), cols), |(_, col)| Some(col * 2 / cols));
+        (map, ownership)
+    }
+
+    #[test]
+    fn newcomer_takes_a_fair_share() {
+        let (map, mut ownership) = halves();
+        let context = context_of(&map);
+        let owned = ownership.count_owned();
+        let parameters = JoinParameters {
+            robot: 2,
+            balance: 1.0,
+        };
+
+        let (taken, lost) = join(&mut ownership, &map, &context, &parameters);
+
+        assert_eq!(taken, owned / 3);
+        assert_eq!(ownership.cells_of(2).len(), taken);
+        assert_eq!(lost.iter().map(|h| h.cells).sum::<usize>(), taken);
+        assert_eq!(ownership.count_owned(), owned);
+    }
+
+    #[test]
+    fn nothing_changes_without_balance() {
+        let (map, mut ownership) = halves();
+        let context = context_of(&map);
+        let parameters = JoinParameters {
+            robot: 2,
+            balance: 0.0,
+        };
+
+        let (taken, lost) = join(&mut ownership, &map, &context, &parameters);
+
+        assert_eq!(taken, 0);
+        assert!(lost.is_empty());
+        assert!(ownership.cells_of(2).is_empty());
+    }
+
+    #[test]
+    fn newcomer_only_takes_cells_it_may_enter() {
+        let (map, mut ownership) = halves();
+        let frame = GridFrame::new(map.map());
+        let mut access = vec![Array2::from_elem((frame.rows(), frame.cols()), true); 3];
+        access[2].fill(false);
+        let context = context_of(&map).with_access(Some(access));
+        let parameters = JoinParameters {
+            robot: 2,
+            balance: 1.0,
+        };
+
+        let (taken, _) = join(&mut ownership, &map, &context, &parameters);
+
+        assert_eq!(taken, 0);
+    }
+}
@@ -0,0 +1,150 @@
+//! This module measures the quality of a partition, so the schemes can be
+//! compared with each other.
+
+use local_robot_map::LocationType;
+use serde::Serialize;
+
+use crate::Map;
+
+use super::{components, robots, Context, GridFrame, Neighbourhood, Ownership};
+
+/// The shape of a single robot's region.
+#[derive(Serialize, Debug, Clone)]
+pub struct RegionMetrics {
+    pub area: f64,
+    /// The length of the region's border, holes included.
+    pub perimeter: f64,
+    /// The isoperimetric ratio `4 π area / perimeter²`, being `1` for a disk
+    /// and approaching `0` for elongated or frayed regions.
+    pub compactness: f64,
+    /// The number of connected components (sharing an edge).
+    pub components: usize,
+    /// The distance from the robot to the centroid of its region, if the
+    /// region is not empty.
+    pub centroid_distance: Option<f64>,
+}
+
+/// The quality of a partition.
+#[derive(Debug, Clone)]
+pub struct PartitionMetrics {
+    /// The metrics of each robot's region, `me` first followed by `others` in
+    /// order.
+    pub robots: Vec<RegionMetrics>,
+    /// The largest area divided by the mean area, `1` being perfectly
+    /// balanced. `None` if all regions are empty.
+    pub imbalance: Option<f64>,
+    /// Cells which are to be covered but owned by nobody.
+    pub uncovered: Vec<(usize, usize)>,
+    /// Cells owned by several robots.
+    pub overlapping: Vec<(usize, usize)>,
+}
+
+/// Measure the partition given by `ownership`.
+///
+/// Cells outside the map, already explored or blocked are not to be covered.
+pub fn evaluate(map: &Map, context: &Context, ownership: &Ownership) -> PartitionMetrics {
+    let frame = GridFrame::new(map.map());
+    let (size_x, size_y) = frame.cell_size();
+    let cells = map.map().cells();
+
+    let robots: Vec<RegionMetrics> = robots(map)
+        .enumerate()
+        .map(|(robot, location)| {
+            let owned = ownership.cells_of(robot);
+            let area = owned.len() as f64 * size_x * size_y;
+            // Every side of a cell facing a cell of someone else is a border
+            let perimeter: f64 = owned
+                .iter()
+                .flat_map(|&(row, col)| {
+                    [
+                        (row.checked_sub(1).map(|r| (r, col)), size_x),
+                        (Some((row + 1, col)), size_x),
+                        (col.checked_sub(1).map(|c| (row, c)), size_y),
+                        (Some((row, col + 1)), size_y),
+                    ]
+                })
+                .filter(|(neighbour, _)| match neighbour {
+                    Some((row, col)) if *row < frame.rows() && *col < frame.cols() => {
+                        !ownership.is_owned_by((*row, *col), robot)
+                    }
+                    _ => true,
+                })
+                .map(|(_, length)| length)
+                .sum();
+            let compactness = if perimeter > 0.0 {
+                4.0 * std::f64::consts::PI * area / perimeter.powi(2)
+            } else {
+                0.0
+            };
+            let centroid_distance = (!owned.is_empty()).then(|| {
+                let (x, y) = owned.iter().fold((0.0, 0.0), |(x, y), &(row, col)| {
+                    let cell = frame.location(row, col);
+                    (x + cell.x(), y + cell.y())
+                });
+                let count = owned.len() as f64;
+                let position = location.location();
+                (x / count - position.x()).hypot(y / count - position.y())
+            });
+
+            RegionMetrics {
+                area,
+                perimeter,
+                compactness,
+                components: components(&frame, &owned, Neighbourhood::Four).len(),
+                centroid_distance,
+            }
+        })
+        .collect();
+
+    let mean = robots.iter().map(|r| r.area).sum::<f64>() / robots.len() as f64;
+    let imbalance = (mean > 0.0).then(|| robots.iter().map(|r| r.area).fold(0.0, f64::max) / mean);
+
+    let mut uncovered = Vec::new();
+    let mut overlapping = Vec::new();
+    for (index, cell) in cells.indexed_iter() {
+        match ownership.owners(index).len() {
+            0 if !matches!(cell, LocationType::OutOfMap | LocationType::Explored)
+                && context.blockage(index).is_none() =>
+            {
+                uncovered.push(index)
+            }
+            0 | 1 => {}
+            _ => overlapping.push(index),
+        }
+    }
+
+    PartitionMetrics {
+        robots,
+        imbalance,
+        uncovered,
+        overlapping,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ps::testing::{context_of, square_map};
+
+    #[test]
+    fn hand_made_split_is_measured() {
+        // Both robots own the top of the third column, nobody the rest of it
+        let map = square_map(4.0, &[(0, 0), (0, 3)]);
+        let column = |col: usize| (0..4).map(move |row| (row, col));
+        let mine: Vec<_> = column(0).chain(column(1)).chain([(0, 2)]).collect();
+        let theirs: Vec<_> = column(3).chain([(0, 2)]).collect();
+        let ownership = Ownership::from_regions((4, 4), &[mine, theirs]);
+
+        let metrics = evaluate(&map, &context_of(&map), &ownership);
+
+        let (me, them) = (&metrics.robots[0], &metrics.robots[1]);
+        assert_eq!((me.area, me.perimeter, me.components), (9.0, 14.0, 1));
+        assert_eq!((them.area, them.perimeter, them.components), (5.0, 12.0, 1));
+        assert!((me.compactness - 4.0 * std::f64::consts::PI * 9.0 / 196.0).abs() < 1e-12);
+        let distance = me.centroid_distance.expect("My region is not empty");
+        assert!((distance - 20.0_f64.sqrt() / 3.0).abs() < 1e-12);
+        assert_eq!(metrics.imbalance, Some(9.0 / 7.0));
+        assert_eq!(metrics.uncovered, vec![(1, 2), (2, 2), (3, 2)]);
+        assert_eq!(metrics.overlapping, vec![(0, 2)]);
+    }
+}
@@ -0,0 +1,196 @@
+//! This module keeps track of which robot owns which cell.
+//!
+//! The partitioning schemes only mark the cells assigned to *my* robot. Yet
+//! some post-processing steps need to know who is on the other side of the
+//! border. We obtain this by running the scheme from every robot's point of
+//! view, which is exactly what the other robots do when calling the API.
+
+use local_robot_map::LocationType;
+use ndarray::Array2;
+
+use crate::Map;
+
+use super::{perspective, robots, Context, GridFrame, Scheme};
+
+/// The robots owning each cell of the map.
+#[derive(Debug, Clone)]
+pub struct Ownership {
+    owners: Array2<Vec<usize>>,
+}
+
+impl Ownership {
+    /// Partition the (not yet partitioned) `map` once for every robot.
+    pub fn compute(map: &Map, scheme: Scheme, context: &Context) -> Self {
+        let mut owners = Array2::from_elem(map.map().cells().dim(), Vec::new());
+
+        for robot in 0..robots(map).count() {
+            let partitioned = scheme(perspective(map, robot), &context.perspective(robot));
+            for ((row, col), cell) in partitioned.map().cells().indexed_iter() {
+                if is_assigned(cell) {
+                    owners[[row, col]].push(robot);
+                }
+            }
+        }
+
+        Self { owners }
+    }
+
+    /// Take the `regions` of each robot as they are, e.g. when given by a
+    /// client rather than computed by a scheme.
+    pub fn from_regions(dim: (usize, usize), regions: &[Vec<(usize, usize)>]) -> Self {
+        let mut ownership = Self {
+            owners: Array2::from_elem(dim, Vec::new()),
+        };
+        for (robot, cells) in regions.iter().enumerate() {
+            for &index in cells {
+                ownership.add(index, robot);
+            }
+        }
+        ownership
+    }
+
+    /// The robots owning the cell at `row` and `col`.
+    pub fn owners(&self, (row, col): (usize, usize)) -> &[usize] {
+        &self.owners[[row, col]]
+    }
+
+    pub fn is_owned_by(&self, index: (usize, usize), robot: usize) -> bool {
+        self.owners(index).contains(&robot)
+    }
+
+    pub(crate) fn remove(&mut self, (row, col): (usize, usize), robot: usize) {
+        self.owners[[row, col]].retain(|&owner| owner != robot);
+    }
+
+    pub(crate) fn add(&mut self, (row, col): (usize, usize), robot: usize) {
+        let owners = &mut self.owners[[row, col]];
+        if !owners.contains(&robot) {
+            owners.push(robot);
+            owners.sort_unstable();
+        }
+    }
+
+    /// All cells owned by `robot`, in row-major order.
+    pub fn cells_of(&self, robot: usize) -> Vec<(usize, usize)> {
+        self.owners
+            .indexed_iter()
+            .filter(|(_, owners)| owners.contains(&robot))
+            .map(|(index, _)| index)
+            .collect()
+    }
+
+    /// The number of cells owned by any robot.
+    pub fn count_owned(&self) -> usize {
+        self.owners
+            .iter()
+            .filter(|owners| !owners.is_empty())
+            .count()
+    }
+
+    /// The total importance of each robot's cells (see [`Context::density_at`]).
+    /// Cells owned by several robots are split equally among them.
+    pub fn weights(&self, context: &Context) -> Vec<f64> {
+        let mut weights = vec![0.0; context.ranks().len()];
+        for (index, owners) in self.owners.indexed_iter() {
+            for &robot in owners {
+                weights[robot] += context.density_at(index) / owners.len() as f64;
+            }
+        }
+        weights
+    }
+
+    /// Mark the cells owned by my robot as [`LocationType::Assigned`] and
+    /// release the ones which are not (anymore).
+    pub fn apply(&self, mut map: Map) -> Map {
+        let frame = GridFrame::new(map.map());
+        let changes: Vec<_> = map
+            .map()
+            .cells()
+            .indexed_iter()
+            .filter_map(|(index, cell)| {
+                let owned = self.is_owned_by(index, 0);
+                match (is_assigned(cell), owned) {
+                    (false, true) if cell == &LocationType::Unexplored => {
+                        Some((index, LocationType::Assigned))
+                    }
+                    (true, false) => Some((index, LocationType::Unexplored)),
+                    _ => None,
+                }
+            })
+            .collect();
+
+        for ((row, col), value) in changes {
+            map.map_mut()
+                .set_location(&frame.location(row, col), value)
+                .expect("All locations are in the map");
+        }
+
+        map
+    }
+}
+
+/// Mark the border of my region again using `mark`, since the region changed
+/// after the border was marked. The stale border becomes
+/// [`LocationType::Assigned`] first.
+pub fn remark_border(mut map: Map, mark: fn(Map) -> Map) -> Map {
+    let frame = GridFrame::new(map.map());
+    let border: Vec<(usize, usize)> = map
+        .map()
+        .cells()
+        .indexed_iter()
+        .filter(|(_, cell)| **cell == LocationType::Frontier)
+        .map(|(index, _)| index)
+        .collect();
+    for (row, col) in border {
+        map.map_mut()
+            .set_location(&frame.location(row, col), LocationType::Assigned)
+            .expect("All locations are in the map");
+    }
+    mark(map)
+}
+
+/// Whether a cell belongs to the region of the robot the map was partitioned
+/// for. Note that [`LocationType::Frontier`] marks the border of that region.
+pub(crate) fn is_assigned(cell: &LocationType) -> bool {
+    matches!(cell, LocationType::Assigned | LocationType::Frontier)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ps::bydistance_contours;
+    use crate::ps::testing::{context_of, ownership_of, square_map};
+
+    #[test]
+    fn border_follows_the_region() {
+        // Me on the left and the other robot on the right half of the inner
+        // four by four cells, since only enclosed regions have a contour
+        let mut map = square_map(6.0, &[(1, 1), (1, 4)]);
+        let frame = GridFrame::new(map.map());
+        let inner = |(row, col): (usize, usize)| (1..5).contains(&row) && (1..5).contains(&col);
+        for (row, col) in (0..6).flat_map(|row| (0..6).map(move |col| (row, col))) {
+            if !inner((row, col)) {
+                map.map_mut()
+                    .set_location(&frame.location(row, col), LocationType::OutOfMap)
+                    .expect("All locations are in the map");
+            }
+        }
+        let context = context_of(&map);
+        let map = bydistance_contours(map, &context);
+        let mark = context
+            .diagnostics()
+            .mark_border
+            .expect("The scheme marks the border");
+        // I take over the third inner column
+        let ownership = ownership_of((6, 6), |index| {
+            inner(index).then_some(usize::from(index.1 > 3))
+        });
+
+        let map = remark_border(ownership.apply(map), mark);
+
+        let cells = map.map().cells();
+        assert!((1..5).all(|row| cells[[row, 3]] == LocationType::Frontier));
+        assert!((2..4).all(|row| cells[[row, 2]] == LocationType::Assigned));
+        assert!((1..5).all(|row| cells[[row, 4]] == LocationType::Unexplored));
+    }
+}
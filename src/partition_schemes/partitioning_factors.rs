@@ -1,15 +1,34 @@
 //! This module provides anything required to deal with different *factors* that
 //! can influence the partitioning.
 
+use std::collections::HashMap;
+
 use serde::Deserialize;
 
-#[derive(Deserialize, Debug)]
+#[derive(Deserialize, Debug, Clone)]
 pub struct Factors {
     speed: f64,
+    /// Multiplies the traversal cost of the given terrain classes.
+    #[serde(default)]
+    terrain: HashMap<String, f64>,
 }
 
 impl Factors {
     pub fn speed(&self) -> f64 {
         self.speed
     }
+
+    /// Whether the speed and all terrain multipliers are finite and strictly
+    /// positive, which anything dividing by them or sharing the map according
+    /// to them relies on.
+    pub fn is_valid(&self) -> bool {
+        std::iter::once(&self.speed)
+            .chain(self.terrain.values())
+            .all(|value| value.is_finite() && *value > 0.0)
+    }
+
+    /// The multiplier of a terrain class' traversal cost, `1` if unspecified.
+    pub fn terrain_multiplier(&self, class: &str) -> f64 {
+        self.terrain.get(class).copied().unwrap_or(1.0)
+    }
 }
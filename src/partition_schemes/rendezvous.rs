@@ -0,0 +1,136 @@
+//! This module proposes where robots meet, e.g. to exchange their maps over
+//! short-range radios.
+
+use local_robot_map::RealWorldLocation;
+use serde::Serialize;
+
+use crate::Map;
+
+use super::{border_cells, robots, travel_costs, Context, GridFrame, Ownership};
+
+/// Where two neighbouring robots meet.
+#[derive(Debug, Clone, Copy)]
+pub struct Rendezvous {
+    /// The indices of both robots, the lower one first.
+    pub robots: (usize, usize),
+    pub cell: (usize, usize),
+    /// The travel time of each robot to the cell (see [`travel_costs`]).
+    pub times: (f64, f64),
+}
+
+/// How long a robot needs to get to the base station, if it can get there.
+#[derive(Serialize, Debug, Clone, Copy)]
+pub struct BaseVisit {
+    pub robot: usize,
+    pub time: Option<f64>,
+}
+
+/// The base station and how long each robot needs to get there.
+#[derive(Debug, Clone)]
+pub struct BaseStation {
+    pub cell: (usize, usize),
+    pub visits: Vec<BaseVisit>,
+}
+
+/// The meeting points of the robots.
+#[derive(Debug, Clone)]
+pub struct Meetings {
+    pub pairs: Vec<Rendezvous>,
+    /// Only provided along with a base station inside the map.
+    pub base: Option<BaseStation>,
+}
+
+/// Propose one rendezvous cell per pair of neighbouring robots, and compute
+/// how long each robot needs to get to the `base` station if there is one
+/// inside the map.
+///
+/// The rendezvous cell lies on both robots' shared border (on either side) and
+/// minimizes the longer of both travel times, then their sum. Pairs which
+/// cannot reach any common border cell are left out.
+pub fn rendezvous(
+    map: &Map,
+    context: &Context,
+    ownership: &Ownership,
+    base: Option<&RealWorldLocation>,
+) -> Meetings {
+    let frame = GridFrame::new(map.map());
+    let costs: Vec<_> = (0..robots(map).count())
+        .map(|robot| travel_costs(map, context, robot))
+        .collect();
+
+    let pairs = border_cells(ownership, &frame)
+        .into_iter()
+        .filter_map(|((a, b), cells)| {
+            cells
+                .into_iter()
+                .flat_map(|(here, there)| [here, there])
+                .map(|cell| (cell, (costs[a][cell], costs[b][cell])))
+                .filter(|(_, (a, b))| a.is_finite() && b.is_finite())
+                .min_by(|(_, x), (_, y)| {
+                    x.0.max(x.1)
+                        .total_cmp(&y.0.max(y.1))
+                        .then_with(|| (x.0 + x.1).total_cmp(&(y.0 + y.1)))
+                })
+                .map(|(cell, times)| Rendezvous {
+                    robots: (a, b),
+                    cell,
+                    times,
+                })
+        })
+        .collect();
+
+    let base = base
+        .and_then(|base| frame.index(base))
+        .map(|cell| BaseStation {
+            cell,
+            visits: costs
+                .iter()
+                .enumerate()
+                .map(|(robot, costs)| BaseVisit {
+                    robot,
+                    time: Some(costs[cell]).filter(|time| time.is_finite()),
+                })
+                .collect(),
+        });
+
+    Meetings { pairs, base }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ps::testing::{context_of, ownership_of, square_map};
+
+    #[test]
+    fn neighbours_meet_where_neither_waits_long() {
+        let map = square_map(6.0, &[(2, 0), (2, 4)]);
+        let frame = GridFrame::new(map.map());
+        let ownership = ownership_of((6, 6), |(_, col)| Some(usize::from(col > 2)));
+        let base = frame.location(0, 0);
+
+        let meetings = rendezvous(&map, &context_of(&map), &ownership, Some(&base));
+
+        // Both robots are two cells away from the left side of the border
+        assert_eq!(meetings.pairs.len(), 1);
+        let pair = meetings.pairs[0];
+        assert_eq!(pair.robots, (0, 1));
+        assert_eq!(pair.cell, (2, 2));
+        assert_eq!(pair.times, (2.0, 2.0));
+        let base = meetings.base.expect("The base station is in the map");
+        assert_eq!(base.cell, (0, 0));
+        let times: Vec<_> = base.visits.iter().map(|visit| visit.time).collect();
+        assert_eq!(times, [Some(2.0), Some(2.0 + 2.0 * 2f64.sqrt())]);
+    }
+
+    #[test]
+    fn base_stations_outside_the_map_are_left_out() {
+        let map = square_map(6.0, &[(2, 0), (2, 4)]);
+        let ownership = ownership_of((6, 6), |(_, col)| Some(usize::from(col > 2)));
+        let base = RealWorldLocation::from_xyz(-10.0, -10.0, 0.0);
+
+        let meetings = rendezvous(&map, &context_of(&map), &ownership, Some(&base));
+
+        assert_eq!(meetings.pairs.len(), 1);
+        assert!(meetings.base.is_none());
+    }
+}
@@ -0,0 +1,370 @@
+//! This module assigns exploration targets to the robots.
+//!
+//! Unlike [`super::assign_frontiers`], every robot gets (at most) one frontier
+//! segment as its next target and every segment is targeted by a limited number
+//! of robots. The assignment minimizes the total travel cost, reduced by a
+//! bonus for the information gained at large segments.
+
+use ndarray::Array2;
+use serde::Deserialize;
+
+use crate::Map;
+
+use super::{closest_cell, cost, robots, travel_costs, Context, FrontierSegment, GridFrame};
+
+/// The parameters of the target assignment.
+#[derive(Deserialize, Debug, Default, Clone, Copy)]
+#[serde(default)]
+pub struct TargetParameters {
+    pub metric: Metric,
+    pub solver: Solver,
+    /// The cost is reduced by this much for every cell of the segment.
+    pub gain_weight: f64,
+    /// How many robots may target the same segment, at least `1` and at most
+    /// the number of robots.
+    pub capacity: usize,
+}
+
+impl TargetParameters {
+    /// The largest gain weight (in absolute terms) which may be requested.
+    pub const MAX_GAIN_WEIGHT: f64 = 1e6;
+
+    /// Whether the costs stay finite and the solver terminates: a finite gain
+    /// weight of at most [`Self::MAX_GAIN_WEIGHT`] and, for the auction, a
+    /// finite and non-negative epsilon.
+    pub fn is_valid(&self) -> bool {
+        let epsilon = match self.solver {
+            Solver::Hungarian => true,
+            Solver::Auction { epsilon } => epsilon.is_finite() && epsilon >= 0.0,
+        };
+        self.gain_weight.abs() <= Self::MAX_GAIN_WEIGHT && epsilon
+    }
+}
+
+/// How the travel cost of a robot to a target is measured.
+#[derive(Deserialize, Debug, Default, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum Metric {
+    /// The straight line distance divided by the robot's speed.
+    #[default]
+    Euclidean,
+    /// The cost along the cheapest path (see [`travel_costs`]).
+    Path,
+}
+
+/// How the assignment problem is solved.
+#[derive(Deserialize, Debug, Default, Clone, Copy, PartialEq)]
+#[serde(tag = "rule", rename_all = "snake_case")]
+pub enum Solver {
+    /// Optimal, using the Hungarian algorithm.
+    #[default]
+    Hungarian,
+    /// Approximate, using the auction algorithm. The lower `epsilon` (relative
+    /// to the largest cost), the closer to the optimum but the slower.
+    Auction { epsilon: f64 },
+}
+
+/// The target of a robot.
+#[derive(Debug, Clone)]
+pub struct Target {
+    /// Index of the frontier segment.
+    pub segment: usize,
+    /// The segment's cell closest to its centroid.
+    pub cell: (usize, usize),
+    /// The travel cost reduced by the information gain.
+    pub cost: f64,
+}
+
+/// Assign a target to each robot, if any is reachable (and left).
+///
+/// As many robots as possible get a target, the total cost of which is then
+/// minimized.
+pub fn assign_targets(
+    map: &Map,
+    context: &Context,
+    segments: &[FrontierSegment],
+    parameters: &TargetParameters,
+) -> Vec<Option<Target>> {
+    let frame = GridFrame::new(map.map());
+    let cells: Vec<(usize, usize)> = segments
+        .iter()
+        .map(|segment| closest_cell(&frame, segment))
+        .collect();
+
+    // Every segment provides a slot for each robot which may target it
+    let robots: Vec<_> = robots(map).collect();
+    let capacity = parameters.capacity.clamp(1, robots.len());
+    let path_costs: Vec<Array2<f64>> = match parameters.metric {
+        Metric::Path => (0..robots.len())
+            .map(|robot| travel_costs(map, context, robot))
+            .collect(),
+        Metric::Euclidean => Vec::new(),
+    };
+    let costs = Array2::from_shape_fn(
+        (robots.len(), segments.len() * capacity),
+        |(robot, slot)| {
+            let segment = slot / capacity;
+            let (row, col) = cells[segment];
+            if !context.allows((row, col), robot) {
+                return f64::INFINITY;
+            }
+            let travel = match parameters.metric {
+                Metric::Euclidean => cost(robots[robot], &frame.location(row, col)),
+                Metric::Path => path_costs[robot][[row, col]],
+            };
+            travel - parameters.gain_weight * segments[segment].cells.len() as f64
+        },
+    );
+
+    let slots = match parameters.solver {
+        Solver::Hungarian => hungarian(&costs),
+        Solver::Auction { epsilon } => auction(&costs, epsilon),
+    };
+    slots
+        .into_iter()
+        .enumerate()
+        .map(|(robot, slot)| {
+            let slot = slot.filter(|&slot| costs[[robot, slot]].is_finite())?;
+            Some(Target {
+                segment: slot / capacity,
+                cell: cells[slot / capacity],
+                cost: costs[[robot, slot]],
+            })
+        })
+        .collect()
+}
+
+/// The largest finite cost in absolute terms.
+fn largest(costs: &Array2<f64>) -> f64 {
+    costs
+        .iter()
+        .filter(|cost| cost.is_finite())
+        .fold(0.0, |largest: f64, cost| largest.max(cost.abs()))
+}
+
+/// A cost exceeding any finite total, such that leaving a robot without target
+/// (or giving it an unreachable one) is the last resort.
+fn penalty(costs: &Array2<f64>) -> f64 {
+    let (rows, cols) = costs.dim();
+    1.0 + 2.0 * rows.max(cols) as f64 * largest(costs)
+}
+
+/// The optimal slot of each robot, using the Hungarian algorithm with
+/// potentials.
+///
+/// Inspired by: <https://cp-algorithms.com/graph/hungarian-algorithm.html>
+fn hungarian(costs: &Array2<f64>) -> Vec<Option<usize>> {
+    let (rows, slots) = costs.dim();
+    let penalty = penalty(costs);
+    // Dummy slots let robots go without target if there are too few slots
+    let cols = slots.max(rows);
+    let cost = |row: usize, col: usize| -> f64 {
+        if col < slots && costs[[row, col]].is_finite() {
+            costs[[row, col]]
+        } else {
+            penalty
+        }
+    };
+
+    // Indices are 1-based, 0 being a sentinel
+    let mut u = vec![0.0; rows + 1];
+    let mut v = vec![0.0; cols + 1];
+    let mut owner = vec![0; cols + 1];
+    let mut way = vec![0; cols + 1];
+    for row in 1..=rows {
+        owner[0] = row;
+        let mut col0 = 0;
+        let mut min_v = vec![f64::INFINITY; cols + 1];
+        let mut used = vec![false; cols + 1];
+        loop {
+            used[col0] = true;
+            let row0 = owner[col0];
+            let (mut delta, mut col1) = (f64::INFINITY, 0);
+            for col in (1..=cols).filter(|&col| !used[col]) {
+                let reduced = cost(row0 - 1, col - 1) - u[row0] - v[col];
+                if reduced < min_v[col] {
+                    min_v[col] = reduced;
+                    way[col] = col0;
+                }
+                if min_v[col] < delta {
+                    delta = min_v[col];
+                    col1 = col;
+                }
+            }
+            for (col, &used) in used.iter().enumerate() {
+                if used {
+                    u[owner[col]] += delta;
+                    v[col] -= delta;
+                } else {
+                    min_v[col] -= delta;
+                }
+            }
+            col0 = col1;
+            if owner[col0] == 0 {
+                break;
+            }
+        }
+        loop {
+            let col1 = way[col0];
+            owner[col0] = owner[col1];
+            col0 = col1;
+            if col0 == 0 {
+                break;
+            }
+        }
+    }
+
+    let mut assigned = vec![None; rows];
+    for (col, &row) in owner.iter().enumerate().skip(1) {
+        if row != 0 && col <= slots {
+            assigned[row - 1] = Some(col - 1);
+        }
+    }
+    assigned
+}
+
+/// A nearly optimal slot of each robot, using the auction algorithm.
+///
+/// Every robot additionally bids on a private dummy slot, which stands for
+/// going without target and guarantees termination.
+fn auction(costs: &Array2<f64>, epsilon: f64) -> Vec<Option<usize>> {
+    let (rows, slots) = costs.dim();
+    let penalty = penalty(costs);
+    let epsilon = epsilon.max(f64::EPSILON) * largest(costs).max(1.0);
+    let benefit = |row: usize, col: usize| -> f64 {
+        if col < slots {
+            -costs[[row, col]]
+        } else if col - slots == row {
+            -penalty
+        } else {
+            f64::NEG_INFINITY
+        }
+    };
+
+    let mut prices = vec![0.0; slots + rows];
+    let mut owner: Vec<Option<usize>> = vec![None; slots + rows];
+    let mut assigned: Vec<Option<usize>> = vec![None; rows];
+    let mut unassigned: Vec<usize> = (0..rows).rev().collect();
+    while let Some(row) = unassigned.pop() {
+        let (mut best, mut best_value, mut second_value) =
+            (row + slots, f64::NEG_INFINITY, f64::NEG_INFINITY);
+        for (col, price) in prices.iter().enumerate() {
+            let value = benefit(row, col) - price;
+            if value > best_value {
+                (best, best_value, second_value) = (col, value, best_value);
+            } else if value > second_value {
+                second_value = value;
+            }
+        }
+
+        // With a single option, outbidding by epsilon is enough
+        let margin = if second_value.is_finite() {
+            best_value - second_value
+        } else {
+            0.0
+        };
+        prices[best] += margin + epsilon;
+        if let Some(previous) = owner[best].replace(row) {
+            assigned[previous] = None;
+            unassigned.push(previous);
+        }
+        assigned[row] = Some(best);
+    }
+
+    assigned
+        .into_iter()
+        .map(|col| col.filter(|&col| col < slots))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use ndarray::array;
+
+    use super::*;
+    use crate::ps::testing::{context_of, square_map};
+
+    #[test]
+    fn capacity_is_limited_to_the_robots() {
+        let map = square_map(6.0, &[(0, 0), (0, 1)]);
+        let frame = GridFrame::new(map.map());
+        let segment = FrontierSegment {
+            cells: vec![(5, 5)],
+            centroid: frame.location(5, 5),
+            robot: None,
+        };
+        let parameters = TargetParameters {
+            capacity: usize::MAX,
+            ..TargetParameters::default()
+        };
+
+        let targets = assign_targets(&map, &context_of(&map), &[segment], &parameters);
+
+        assert!(targets
+            .iter()
+            .all(|target| matches!(target, Some(t) if t.segment == 0)));
+    }
+
+    #[test]
+    fn parameters_are_validated() {
+        let valid = TargetParameters {
+            gain_weight: TargetParameters::MAX_GAIN_WEIGHT,
+            solver: Solver::Auction { epsilon: 0.0 },
+            ..TargetParameters::default()
+        };
+        let invalid = [
+            TargetParameters {
+                gain_weight: f64::NAN,
+                ..valid
+            },
+            TargetParameters {
+                gain_weight: -2.0 * TargetParameters::MAX_GAIN_WEIGHT,
+                ..valid
+            },
+            TargetParameters {
+                solver: Solver::Auction {
+                    epsilon: f64::INFINITY,
+                },
+                ..valid
+            },
+        ];
+
+        assert!(valid.is_valid());
+        assert!(invalid.iter().all(|parameters| !parameters.is_valid()));
+    }
+
+    #[test]
+    fn hungarian_finds_the_optimum() {
+        let costs = array![[4.0, 1.0, 3.0], [2.0, 0.0, 5.0], [3.0, 2.0, 2.0]];
+
+        assert_eq!(hungarian(&costs), vec![Some(1), Some(0), Some(2)]);
+    }
+
+    #[test]
+    fn hungarian_avoids_unreachable_slots() {
+        let costs = array![[f64::INFINITY, 1.0], [1.0, 2.0]];
+
+        assert_eq!(hungarian(&costs), vec![Some(1), Some(0)]);
+    }
+
+    #[test]
+    fn hungarian_leaves_robots_without_slot() {
+        let costs = array![[1.0], [5.0]];
+
+        assert_eq!(hungarian(&costs), vec![Some(0), None]);
+    }
+
+    #[test]
+    fn auction_finds_the_optimum() {
+        let costs = array![[4.0, 1.0, 3.0], [2.0, 0.0, 5.0], [3.0, 2.0, 2.0]];
+
+        assert_eq!(auction(&costs, 1e-3), hungarian(&costs));
+    }
+
+    #[test]
+    fn auction_leaves_robots_without_slot() {
+        let costs = array![[1.0], [5.0]];
+
+        assert_eq!(auction(&costs, 1e-3), vec![Some(0), None]);
+    }
+}
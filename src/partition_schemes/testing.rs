@@ -0,0 +1,53 @@
+//! Helpers shared by the unit tests of the partitioning schemes.
+
+use local_robot_map::{AxisResolution, LocalMap, PolygonMap, RealWorldLocation};
+
+use crate::{Map, RobotLocation};
+
+use super::{robots, Context, GridFrame, Ownership};
+
+/// A square map of `size` by `size` metres with cells of one metre, holding a
+/// robot on each of the given cells (`row`, `col`), `me` being the first one.
+pub(crate) fn square_map(size: f64, positions: &[(usize, usize)]) -> Map {
+    let vertices = [(0.0, 0.0), (size, 0.0), (size, size), (0.0, size)]
+        .into_iter()
+        .map(|(x, y)| RealWorldLocation::from_xyz(x, y, 0.0))
+        .collect();
+    let cells = PolygonMap::new_explored(vertices, None)
+        .expect("A square has enough vertices")
+        .to_cell_map(AxisResolution::new(1.0, 1.0, 1.0));
+    let frame = GridFrame::new(&cells);
+    let mut robots = positions
+        .iter()
+        .map(|&(row, col)| RobotLocation::new(frame.location(row, col), None));
+    let me = robots.next().expect("There is at least one robot");
+    LocalMap::new_noexpand_nooutofmap(cells, me, robots.collect())
+        .expect("All robots are in the map area")
+}
+
+/// The context of a map without any ids, hence ranking the robots by their
+/// position.
+pub(crate) fn context_of(map: &Map) -> Context {
+    Context::new(
+        vec![None; robots(map).count()],
+        robots(map).map(|robot| robot.location()).collect(),
+    )
+}
+
+/// The ownership of a grid with the given dimensions, `owner` telling which
+/// robot (if any) owns each cell.
+pub(crate) fn ownership_of(
+    (rows, cols): (usize, usize),
+    owner: impl Fn((usize, usize)) -> Option<usize>,
+) -> Ownership {
+    let mut regions: Vec<Vec<(usize, usize)>> = Vec::new();
+    for index in (0..rows).flat_map(|row| (0..cols).map(move |col| (row, col))) {
+        if let Some(robot) = owner(index) {
+            if regions.len() <= robot {
+                regions.resize(robot + 1, Vec::new());
+            }
+            regions[robot].push(index);
+        }
+    }
+    Ownership::from_regions((rows, cols), &regions)
+}
@@ -0,0 +1,136 @@
+//! This module decides who gets a cell when several robots are equally well
+//! suited for it.
+//!
+//! Every robot calls the API with itself as `me`, which means that every robot
+//! has to come to the same conclusion independently. The rules provided here
+//! are therefore purely deterministic: they only depend on the robots' ranks
+//! (see [`super::Context`]) and on the cell's row and column, which are the
+//! same for every robot while its real-world location may differ slightly.
+
+use serde::Deserialize;
+
+/// Costs closer to each other than this are considered to be equal.
+///
+/// Distances of symmetric layouts are rarely *exactly* equal once they went
+/// through a square root, hence the tolerance.
+const TIE_EPSILON: f64 = 1e-9;
+
+/// The rule used to settle a tie between robots with the same cost for a cell.
+#[derive(Deserialize, Debug, Default, Clone, Copy, PartialEq)]
+#[serde(tag = "rule", rename_all = "snake_case")]
+pub enum TieBreak {
+    /// Nobody gets the cell. This leaves gaps in the partition.
+    Unassigned,
+    /// The robot with the lowest id (or rank) gets the cell.
+    #[default]
+    LowestId,
+    /// One of the tied robots is chosen pseudo-randomly. The choice only
+    /// depends on the seed and the cell, hence every robot draws the same one.
+    SeededRandom { seed: u64 },
+    /// All tied robots get the cell, i.e. the boundary is shared.
+    Shared,
+}
+
+impl TieBreak {
+    /// Return the indices of the robots which own the cell at `row` and `col`.
+    ///
+    /// `costs` holds the cost of each robot for the cell and `ranks` the
+    /// corresponding rank of each robot; both are indexed the same way. The
+    /// result is empty if the cell is left unassigned and only holds more than
+    /// one robot in case of [`TieBreak::Shared`].
+    pub fn owners(&self, costs: &[f64], ranks: &[usize], (row, col): (usize, usize)) -> Vec<usize> {
+        let best = costs.iter().copied().fold(f64::INFINITY, f64::min);
+        if !best.is_finite() {
+            return Vec::new();
+        }
+
+        let mut candidates: Vec<usize> = costs
+            .iter()
+            .enumerate()
+            .filter(|(_, &cost)| cost - best <= TIE_EPSILON * best.abs().max(1.0))
+            .map(|(robot, _)| robot)
+            .collect();
+        if candidates.len() == 1 {
+            return candidates;
+        }
+        candidates.sort_by_key(|&robot| ranks[robot]);
+
+        match self {
+            TieBreak::Unassigned => Vec::new(),
+            TieBreak::LowestId => vec![candidates[0]],
+            TieBreak::SeededRandom { seed } => {
+                let hash = mix(mix(*seed) ^ row as u64);
+                let hash = mix(hash ^ col as u64);
+                vec![candidates[(hash % candidates.len() as u64) as usize]]
+            }
+            TieBreak::Shared => candidates,
+        }
+    }
+}
+
+/// The *SplitMix64* finalizer.
+///
+/// We deliberately avoid [`std::collections::hash_map::DefaultHasher`] since
+/// its output is not guaranteed to be stable across Rust versions, while robots
+/// rely on drawing the same numbers.
+///
+/// See: <https://prng.di.unimi.it/splitmix64.c>
+fn mix(value: u64) -> u64 {
+    let mut z = value.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Two robots at the same cost, the second one ranked first.
+    const COSTS: [f64; 2] = [2.0, 2.0 + TIE_EPSILON];
+    const RANKS: [usize; 2] = [1, 0];
+
+    #[test]
+    fn lowest_rank_wins() {
+        assert_eq!(TieBreak::LowestId.owners(&COSTS, &RANKS, (0, 0)), vec![1]);
+    }
+
+    #[test]
+    fn ties_are_unassigned_or_shared() {
+        assert!(TieBreak::Unassigned
+            .owners(&COSTS, &RANKS, (0, 0))
+            .is_empty());
+        assert_eq!(TieBreak::Shared.owners(&COSTS, &RANKS, (0, 0)), vec![1, 0]);
+    }
+
+    #[test]
+    fn no_tie_without_equal_costs() {
+        for rule in [TieBreak::Unassigned, TieBreak::Shared, TieBreak::LowestId] {
+            assert_eq!(rule.owners(&[1.0, 2.0], &RANKS, (0, 0)), vec![0]);
+        }
+        assert!(TieBreak::LowestId
+            .owners(&[f64::INFINITY; 2], &RANKS, (0, 0))
+            .is_empty());
+    }
+
+    #[test]
+    fn every_robot_draws_the_same() {
+        let rule = TieBreak::SeededRandom { seed: 7 };
+        let cells: Vec<(usize, usize)> = (0..8)
+            .flat_map(|row| (0..8).map(move |col| (row, col)))
+            .collect();
+
+        // The other robot lists both robots the other way around
+        let mine: Vec<usize> = cells
+            .iter()
+            .map(|&cell| rule.owners(&COSTS, &RANKS, cell)[0])
+            .collect();
+        let theirs: Vec<usize> = cells
+            .iter()
+            .map(|&cell| 1 - rule.owners(&COSTS, &[0, 1], cell)[0])
+            .collect();
+
+        assert_eq!(mine, theirs);
+        assert!(mine.contains(&0) && mine.contains(&1));
+    }
+}
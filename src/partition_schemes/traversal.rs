@@ -0,0 +1,187 @@
+//! This module provides anything required to compute how long it takes a robot
+//! to travel across the map, taking the terrain into account.
+
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+
+use local_robot_map::LocationType;
+use ndarray::Array2;
+
+use crate::{Map, RobotLocation};
+
+use super::{robots, Context, GridFrame, Neighbourhood};
+
+/// The cost of traversing each cell of the map.
+///
+/// Cells may belong to a named terrain class (e.g. `"gravel"`), which allows
+/// robots to scale the cost of that class using their [`super::Factors`].
+#[derive(Debug, Clone)]
+pub struct Terrain {
+    costs: Array2<f64>,
+    classes: Array2<Option<usize>>,
+    names: Vec<String>,
+}
+
+impl Terrain {
+    /// Creates a new [`Terrain`].
+    ///
+    /// `classes` refers to the `names` of the terrain classes by index.
+    pub fn new(costs: Array2<f64>, classes: Array2<Option<usize>>, names: Vec<String>) -> Self {
+        Self {
+            costs,
+            classes,
+            names,
+        }
+    }
+
+    /// The cost for a robot to traverse the cell at `row` and `col`.
+    pub fn cost(&self, (row, col): (usize, usize), robot: &RobotLocation) -> f64 {
+        let cost = self.costs[[row, col]];
+        match self.classes[[row, col]] {
+            Some(class) => {
+                let name = &self.names[class];
+                cost * robot
+                    .parameters()
+                    .as_ref()
+                    .map_or(1.0, |f| f.terrain_multiplier(name))
+            }
+            None => cost,
+        }
+    }
+}
+
+/// The time it takes a robot to reach every cell of the map.
+///
+/// The costs are accumulated along the cheapest path (*Dijkstra*) using an
+/// 8-neighbourhood, where entering a cell costs its traversal cost times the
+/// distance travelled, divided by the robot's speed. Cells outside the map or
+/// which the robot may not enter (see [`Context::allows`]), including obstacles
+/// and no-go zones, cannot be traversed and are unreachable (i.e. infinite
+/// cost). Neither can the robot cut the corner of such a cell diagonally. If
+/// the map consists of several areas, robots may however travel through the
+/// gaps between them.
+pub fn travel_costs(map: &Map, context: &Context, index: usize) -> Array2<f64> {
+    cheapest_paths(map, context, index, true)
+}
+
+/// The length of the shortest path of a robot to every cell of the map,
+/// divided by its speed.
+///
+/// Same as [`travel_costs`], except that every cell is as cheap to traverse as
+/// any other. Without anything in the way, this is the straight line distance
+/// (up to the 8-neighbourhood's detours), e.g. as used by [`super::cost`].
+pub fn path_distances(map: &Map, context: &Context, index: usize) -> Array2<f64> {
+    cheapest_paths(map, context, index, false)
+}
+
+/// Dijkstra's algorithm for [`travel_costs`] and [`path_distances`], weighting
+/// each step by the traversal cost of the entered cell if `terrain` is set.
+fn cheapest_paths(map: &Map, context: &Context, index: usize, terrain: bool) -> Array2<f64> {
+    let frame = GridFrame::new(map.map());
+    let mut costs = Array2::from_elem((frame.rows(), frame.cols()), f64::INFINITY);
+    let robot = robots(map).nth(index).expect("The robot is in the map");
+    let Some(start) = frame.index(robot.location()) else {
+        return costs;
+    };
+    let speed = robot.parameters().as_ref().map_or(1.0, |f| f.speed());
+    let (size_x, size_y) = frame.cell_size();
+    let passable = |(row, col): (usize, usize)| {
+        (map.map().cells()[[row, col]] != LocationType::OutOfMap || context.has_areas())
+            && context.allows((row, col), index)
+    };
+
+    costs[[start.0, start.1]] = 0.0;
+    let mut queue = BinaryHeap::from([Visit {
+        cost: 0.0,
+        cell: start,
+    }]);
+
+    while let Some(Visit { cost, cell }) = queue.pop() {
+        if cost > costs[[cell.0, cell.1]] {
+            continue;
+        }
+        for (row, col) in frame.neighbours(cell, Neighbourhood::Eight) {
+            // A diagonal step passes both cells sharing its corner
+            if !passable((row, col)) || !passable((row, cell.1)) || !passable((cell.0, col)) {
+                continue;
+            }
+            let weight = if terrain {
+                context.traversal_cost((row, col), robot)
+            } else {
+                1.0
+            };
+            let step = ((row.abs_diff(cell.0) as f64 * size_y)
+                .hypot(col.abs_diff(cell.1) as f64 * size_x))
+                * weight
+                / speed;
+            if cost + step < costs[[row, col]] {
+                costs[[row, col]] = cost + step;
+                queue.push(Visit {
+                    cost: cost + step,
+                    cell: (row, col),
+                });
+            }
+        }
+    }
+
+    costs
+}
+
+/// An entry of the priority queue, the cheapest being the greatest.
+#[derive(PartialEq)]
+struct Visit {
+    cost: f64,
+    cell: (usize, usize),
+}
+
+impl Eq for Visit {}
+
+impl Ord for Visit {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other
+            .cost
+            .total_cmp(&self.cost)
+            .then_with(|| self.cell.cmp(&other.cell))
+    }
+}
+
+impl PartialOrd for Visit {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ps::testing::{context_of, square_map};
+    use crate::ps::Blockage;
+
+    #[test]
+    fn corners_of_blocked_cells_are_not_cut() {
+        let map = square_map(3.0, &[(0, 0)]);
+        let mut blocked = Array2::from_elem((3, 3), None);
+        blocked[[0, 1]] = Some(Blockage::Obstacle);
+        let context = context_of(&map).with_blocked(Some(blocked));
+
+        let costs = travel_costs(&map, &context, 0);
+
+        assert_eq!(costs[[1, 1]], 2.0);
+        assert!(costs[[0, 1]].is_infinite());
+        assert_eq!(costs[[0, 2]], 4.0);
+    }
+
+    #[test]
+    fn path_distances_ignore_the_terrain() {
+        let map = square_map(3.0, &[(0, 0)]);
+        let terrain = Terrain::new(
+            Array2::from_elem((3, 3), 5.0),
+            Array2::from_elem((3, 3), None),
+            Vec::new(),
+        );
+        let context = context_of(&map).with_terrain(Some(terrain));
+
+        assert_eq!(travel_costs(&map, &context, 0)[[0, 2]], 10.0);
+        assert_eq!(path_distances(&map, &context, 0)[[0, 2]], 2.0);
+    }
+}
@@ -1,8 +1,15 @@
 mod distance;
 pub use distance::bydistance;
+pub(crate) use distance::cost;
 
 mod distance_frontier;
 pub use distance_frontier::bydistance_frontiers;
 
 mod distance_contour;
 pub use distance_contour::bydistance_contours;
+
+mod path;
+pub use path::bypath;
+
+mod centroidal;
+pub use centroidal::{centroidal_voronoi, CentroidalVoronoiParameters, Deployment};
@@ -0,0 +1,226 @@
+//! Centroidal Voronoi tessellation using Lloyd's algorithm.
+//!
+//! Instead of partitioning around the robots' current positions, the robots are
+//! repeatedly moved to the (density weighted) centroid of their Voronoi region.
+//! The converged positions are where the robots *should* be deployed to
+//! minimize the coverage time. The map is then partitioned using
+//! [`super::distance`] as if the robots were at those positions, while the
+//! robots themselves stay where they are. The converged positions are only
+//! reported along with the [`Deployment`].
+
+use local_robot_map::{LocalMap, LocationType, RealWorldLocation};
+use serde::Deserialize;
+
+use crate::{Map, RobotLocation};
+
+use super::super::{robots, Context, GridFrame};
+use super::distance::cost;
+
+/// The parameters of the [`centroidal_voronoi`] scheme.
+#[derive(Deserialize, Debug, Clone, Copy)]
+#[serde(default)]
+pub struct CentroidalVoronoiParameters {
+    /// Stop after this many iterations, even if the robots are still moving.
+    pub max_iterations: usize,
+    /// Consider the robots to be converged once none of them moves further
+    /// than this (in real-world units) during an iteration.
+    pub tolerance: f64,
+}
+
+impl Default for CentroidalVoronoiParameters {
+    fn default() -> Self {
+        Self {
+            max_iterations: 100,
+            tolerance: 0.01,
+        }
+    }
+}
+
+/// The result of Lloyd's algorithm.
+#[derive(Debug, Clone)]
+pub struct Deployment {
+    pub converged: bool,
+    /// The converged position of each robot.
+    pub generators: Vec<RealWorldLocation>,
+    /// The position of each robot after every iteration, starting with their
+    /// initial positions.
+    pub history: Vec<Vec<RealWorldLocation>>,
+}
+
+/// Move the generators to the centroids of their regions, partition the map at
+/// their positions and record the [`Deployment`].
+pub fn centroidal_voronoi(mut map: Map, context: &Context) -> Map {
+    let parameters = context.centroidal_voronoi();
+    let frame = GridFrame::new(map.map());
+    let cells: Vec<(usize, usize)> = map
+        .map()
+        .cells()
+        .indexed_iter()
+        .filter(|(_, cell)| **cell == LocationType::Unexplored)
+        .map(|(index, _)| index)
+        .filter(|&index| context.allows_anyone(index))
+        .collect();
+    let locations: Vec<RealWorldLocation> = cells
+        .iter()
+        .map(|&(row, col)| frame.location(row, col))
+        .collect();
+    let weights: Vec<f64> = cells
+        .iter()
+        .map(|&index| context.density_at(index))
+        .collect();
+
+    let mut generators: Vec<RobotLocation> = robots(&map).cloned().collect();
+    let mut history = vec![positions(&generators)];
+    let mut converged = false;
+
+    while !converged && history.len() <= parameters.max_iterations {
+        let mut sums = vec![(0.0, 0.0, 0.0); generators.len()];
+        let mut regions: Vec<Vec<usize>> = vec![Vec::new(); generators.len()];
+        for (cell, location) in locations.iter().enumerate() {
+            let robot_cost = |robot: usize| {
+                if context.allows(cells[cell], robot) {
+                    cost(&generators[robot], location)
+                } else {
+                    f64::INFINITY
+                }
+            };
+            let owner = (0..generators.len())
+                .min_by(|&a, &b| {
+                    robot_cost(a)
+                        .total_cmp(&robot_cost(b))
+                        .then_with(|| context.ranks()[a].cmp(&context.ranks()[b]))
+                })
+                .expect("There is at least one robot");
+            let (x, y, weight) = &mut sums[owner];
+            *x += weights[cell] * location.x();
+            *y += weights[cell] * location.y();
+            *weight += weights[cell];
+            regions[owner].push(cell);
+        }
+
+        let mut movement: f64 = 0.0;
+        for (robot, generator) in generators.iter_mut().enumerate() {
+            let (x, y, weight) = sums[robot];
+            if weight <= 0.0 {
+                continue;
+            }
+            // The centroid may lie outside the region (e.g. in a hole), hence
+            // we move to the region's cell closest to it.
+            let centroid = RealWorldLocation::from_xyz(x / weight, y / weight, 0.0);
+            let target = regions[robot]
+                .iter()
+                .map(|&cell| &locations[cell])
+                .min_by(|a, b| {
+                    planar_distance(a, &centroid).total_cmp(&planar_distance(b, &centroid))
+                })
+                .expect("A region with weight has cells");
+            let target =
+                RealWorldLocation::from_xyz(target.x(), target.y(), generator.location().z());
+
+            movement = movement.max(planar_distance(generator.location(), &target));
+            *generator = RobotLocation::new(target, generator.parameters().clone());
+        }
+
+        history.push(positions(&generators));
+        converged = movement <= parameters.tolerance;
+    }
+
+    let mut generators = generators.into_iter();
+    let me = generators.next().expect("My robot is always present");
+    let moved = LocalMap::new_noexpand_nooutofmap(map.map().clone(), me, generators.collect())
+        .expect("All generators are in the map area");
+
+    context.record(|diagnostics| {
+        diagnostics.deployment = Some(Deployment {
+            converged,
+            generators: history.last().cloned().unwrap_or_default(),
+            history,
+        })
+    });
+
+    let partitioned = super::distance::bydistance(moved, context);
+    for ((row, col), _) in partitioned
+        .map()
+        .cells()
+        .indexed_iter()
+        .filter(|(_, cell)| **cell == LocationType::Assigned)
+    {
+        map.map_mut()
+            .set_location(&frame.location(row, col), LocationType::Assigned)
+            .expect("All locations are in the map");
+    }
+
+    map
+}
+
+fn positions(robots: &[RobotLocation]) -> Vec<RealWorldLocation> {
+    robots
+        .iter()
+        .map(|robot| robot.location().clone())
+        .collect()
+}
+
+/// The distance between two locations, ignoring the 3rd dimension.
+fn planar_distance(a: &RealWorldLocation, b: &RealWorldLocation) -> f64 {
+    (a.x() - b.x()).hypot(a.y() - b.y())
+}
+
+#[cfg(test)]
+mod tests {
+    use ndarray::Array2;
+
+    use super::*;
+    use crate::ps::testing::{context_of, square_map};
+
+    fn xy(location: &RealWorldLocation) -> (f64, f64) {
+        (location.x(), location.y())
+    }
+
+    #[test]
+    fn robots_spread_over_the_map() {
+        let map = square_map(6.0, &[(0, 0), (5, 0)]);
+        let frame = GridFrame::new(map.map());
+        let context = context_of(&map);
+
+        let map = centroidal_voronoi(map, &context);
+
+        let deployment = context
+            .diagnostics()
+            .deployment
+            .expect("The deployment is recorded");
+        assert!(deployment.converged);
+        // Ties between the two central columns go to the first cell
+        let generators: Vec<_> = deployment.generators.iter().map(xy).collect();
+        assert_eq!(
+            generators,
+            [xy(&frame.location(1, 2)), xy(&frame.location(4, 2))]
+        );
+        let initial: Vec<_> = deployment.history[0].iter().map(xy).collect();
+        assert_eq!(
+            initial,
+            [xy(&frame.location(0, 0)), xy(&frame.location(5, 0))]
+        );
+        let cells = map.map().cells();
+        assert!(cells
+            .indexed_iter()
+            .all(|((row, _), cell)| (*cell == LocationType::Assigned) == (row < 3)));
+    }
+
+    #[test]
+    fn robots_head_for_the_density() {
+        let map = square_map(5.0, &[(0, 0)]);
+        let frame = GridFrame::new(map.map());
+        let mut density = Array2::zeros((5, 5));
+        density[[3, 3]] = 1.0;
+        let context = context_of(&map).with_density(Some(density));
+
+        centroidal_voronoi(map, &context);
+
+        let deployment = context
+            .diagnostics()
+            .deployment
+            .expect("The deployment is recorded");
+        assert!(deployment.converged);
+        assert_eq!(xy(&deployment.generators[0]), xy(&frame.location(3, 3)));
+    }
+}
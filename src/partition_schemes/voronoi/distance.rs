@@ -2,29 +2,38 @@
 
 use local_robot_map::{Location, MaskMapState};
 use local_robot_map::{LocationType, RealWorldLocation};
+use ndarray::Array2;
 
-use crate::Map;
+use crate::{Map, RobotLocation};
 
-pub fn bydistance(mut map: Map) -> Map {
+use super::super::{path_distances, robots, Context, GridFrame};
+
+/// Assign every cell to the closest robot.
+///
+/// Cells which are blocked are never assigned. Since the straight line to a
+/// cell may cross them, robots travel around them along the shortest path (see
+/// [`path_distances`]) as soon as any cell is blocked.
+pub fn bydistance(mut map: Map, context: &Context) -> Map {
+    let frame = GridFrame::new(map.map());
     let mut cells_to_assign: Vec<RealWorldLocation> = Vec::new();
+    let paths: Option<Vec<Array2<f64>>> = context.blocked().is_some().then(|| {
+        (0..robots(&map).count())
+            .map(|robot| path_distances(&map, context, robot))
+            .collect()
+    });
 
     for cell in map.map().get_map_state(LocationType::Unexplored) {
-        if map.other_positions().is_empty() {
-            cells_to_assign.push(cell.location().clone());
-            continue;
-        }
-        let my_cost = match map.my_robot().parameters() {
-            Some(f) => map.my_position().distance(cell.location()) / f.speed(),
-            None => map.my_position().distance(cell.location()),
-        };
-        let other_costs = map
-            .other_robots()
-            .iter()
-            .map(|robot| match robot.parameters() {
-                Some(f) => robot.location().distance(cell.location()) / f.speed(),
-                None => robot.location().distance(cell.location()),
-            });
-        if other_costs.into_iter().all(|score| my_cost < score) {
+        let index = frame.index(cell.location()).expect("Cell is in the map");
+        let costs: Vec<f64> = robots(&map)
+            .enumerate()
+            .map(|(i, robot)| match &paths {
+                _ if !context.allows(index, i) => f64::INFINITY,
+                Some(paths) => paths[i][index],
+                None => cost(robot, cell.location()),
+            })
+            .collect();
+        let owners = context.tie_break().owners(&costs, context.ranks(), index);
+        if owners.contains(&0) {
             cells_to_assign.push(cell.location().clone());
         }
     }
@@ -37,3 +46,38 @@ pub fn bydistance(mut map: Map) -> Map {
 
     map
 }
+
+/// The cost for a robot to reach a location, i.e. the time it takes to get
+/// there if the robot's speed is known and the distance otherwise.
+pub(crate) fn cost(robot: &RobotLocation, location: &RealWorldLocation) -> f64 {
+    match robot.parameters() {
+        Some(f) => robot.location().distance(location) / f.speed(),
+        None => robot.location().distance(location),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ps::testing::{context_of, square_map};
+    use crate::ps::{Blockage, Terrain};
+
+    #[test]
+    fn blocked_cells_keep_the_distances() {
+        // Only I have to cross the costly column, which must not matter
+        let map = square_map(6.0, &[(2, 0), (2, 5)]);
+        let costs = Array2::from_shape_fn((6, 6), |(_, col)| if col == 1 { 10.0 } else { 1.0 });
+        let terrain = Terrain::new(costs, Array2::from_elem((6, 6), None), Vec::new());
+        let mut blocked = Array2::from_elem((6, 6), None);
+        blocked[[0, 0]] = Some(Blockage::Obstacle);
+        let context = context_of(&map)
+            .with_terrain(Some(terrain))
+            .with_blocked(Some(blocked));
+
+        let map = bydistance(map, &context);
+
+        let cells = map.map().cells();
+        assert!((0..6).all(|row| cells[[row, 2]] == LocationType::Assigned));
+        assert!((0..6).all(|row| cells[[row, 3]] == LocationType::Unexplored));
+    }
+}
@@ -6,8 +6,11 @@ use local_robot_map::{Cell, Coords, LocationType, RealWorldLocation};
 
 use crate::Map;
 
-pub fn bydistance_frontiers(map: Map) -> Map {
-    super::distance::bydistance(map).set_frontiers()
+use super::super::Context;
+
+pub fn bydistance_frontiers(map: Map, context: &Context) -> Map {
+    context.record(|diagnostics| diagnostics.mark_border = Some(Frontiers::set_frontiers));
+    super::distance::bydistance(map, context).set_frontiers()
 }
 
 /// We shall take the liberty of interpreting the [`LocationType::Frontier`] to
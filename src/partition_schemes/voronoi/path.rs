@@ -0,0 +1,83 @@
+//! Same as [`super::distance`], except that the cost of a robot is the time it
+//! takes to travel along the cheapest path to a cell, rather than the straight
+//! line distance. This takes the terrain as well as the shape of the map into
+//! account.
+
+use local_robot_map::{LocationType, RealWorldLocation};
+use ndarray::Array2;
+
+use crate::Map;
+
+use super::super::{robots, travel_costs, Context, GridFrame};
+
+pub fn bypath(mut map: Map, context: &Context) -> Map {
+    let frame = GridFrame::new(map.map());
+    let costs: Vec<Array2<f64>> = (0..robots(&map).count())
+        .map(|robot| travel_costs(&map, context, robot))
+        .collect();
+
+    let cells_to_assign: Vec<RealWorldLocation> = map
+        .map()
+        .cells()
+        .indexed_iter()
+        .filter(|(_, cell)| **cell == LocationType::Unexplored)
+        .filter_map(|((row, col), _)| {
+            let location = frame.location(row, col);
+            let cell_costs: Vec<f64> = costs.iter().map(|c| c[[row, col]]).collect();
+            context
+                .tie_break()
+                .owners(&cell_costs, context.ranks(), (row, col))
+                .contains(&0)
+                .then_some(location)
+        })
+        .collect();
+
+    for location in &cells_to_assign {
+        map.map_mut()
+            .set_location(location, LocationType::Assigned)
+            .expect("All locations are in the map");
+    }
+
+    map
+}
+
+#[cfg(test)]
+mod tests {
+    use local_robot_map::LocalMap;
+
+    use super::*;
+    use crate::ps::testing::{context_of, square_map};
+    use crate::ps::{Factors, Terrain};
+    use crate::RobotLocation;
+
+    #[test]
+    fn terrain_multipliers_favour_the_drone() {
+        // The drone on the right crosses the water in the middle quickly
+        let map = square_map(6.0, &[(2, 0), (2, 5)]);
+        let frame = GridFrame::new(map.map());
+        let drone: Factors = serde_json::from_value(
+            serde_json::json!({ "speed": 1.0, "terrain": { "water": 0.1 } }),
+        )
+        .expect("The factors are valid");
+        let map = LocalMap::new_noexpand_nooutofmap(
+            map.map().clone(),
+            RobotLocation::new(frame.location(2, 0), None),
+            vec![RobotLocation::new(frame.location(2, 5), Some(drone))],
+        )
+        .expect("All robots are in the map area");
+        let water = |col: usize| (1..4).contains(&col);
+        let terrain = Terrain::new(
+            Array2::from_shape_fn((6, 6), |(_, col)| if water(col) { 10.0 } else { 1.0 }),
+            Array2::from_shape_fn((6, 6), |(_, col)| water(col).then_some(0)),
+            vec!["water".to_owned()],
+        );
+        let context = context_of(&map).with_terrain(Some(terrain));
+
+        let map = bypath(map, &context);
+
+        let cells = map.map().cells();
+        assert!(cells
+            .indexed_iter()
+            .all(|((_, col), cell)| (*cell == LocationType::Assigned) == (col == 0)));
+    }
+}
@@ -1,4 +1,8 @@
 mod helpers;
+mod layers;
+mod occupancy;
+mod projection;
+mod sweep;
 mod types;
 
 mod http;
@@ -9,3 +13,6 @@ pub use shared_memory::polygon_handler_shm;
 
 mod filepath;
 pub use filepath::polygon_handler_filepath;
+
+mod multipart;
+pub use multipart::polygon_handler_multipart;
@@ -1,10 +1,8 @@
 use std::time::Instant;
 
 use axum::http::StatusCode;
-use local_robot_map::Algorithm;
-use local_robot_map::PartitionError;
 
-use crate::Map;
+use crate::ps::Scheme;
 
 use super::helpers;
 use super::types;
@@ -28,7 +26,7 @@ use super::types;
 /// - No viable map was provided through the input polygon points
 pub async fn polygon_handler_filepath(
     file_path: String,
-    algorithm: Algorithm<Map>,
+    algorithm: Scheme,
 ) -> Result<StatusCode, (StatusCode, String)> {
     println!("=== Request received! ===");
     println!(">>> Partition map and return all cells (uses file to share data)");
@@ -55,38 +53,38 @@ pub async fn polygon_handler_filepath(
 
     println!("Read data from file ({:?})", now.elapsed());
 
-    let result = match helpers::partition_input_data(data, algorithm) {
-        Ok(map) => {
-            println!("Partitioned map ({:?})", now.elapsed());
-            match serde_json::to_string(&types::OutputData::from_cellmap(map.map())) {
-                Ok(json_string) => {
-                    println!("Converted cellmap to JSON string ({:?})", now.elapsed());
-                    match std::fs::write(&file_path, json_string) {
-                        Ok(_) => {
-                            println!("Wrote data back to file ({:?})", now.elapsed());
-                            Ok(StatusCode::OK)
-                        }
-                        Err(e) => {
-                            return Err((
-                                StatusCode::INTERNAL_SERVER_ERROR,
-                                format!("Could not write file: {e}"),
-                            ))
-                        }
+    let output = helpers::partition_input_data(data, algorithm).and_then(|(map, report)| {
+        println!("Partitioned map ({:?})", now.elapsed());
+        types::OutputData::from_cellmap(map.map(), report.blocked.as_ref()).with_report(report)
+    });
+    let result = match output {
+        Ok(output) => match serde_json::to_string(&output) {
+            Ok(json_string) => {
+                println!("Converted cellmap to JSON string ({:?})", now.elapsed());
+                match std::fs::write(&file_path, json_string) {
+                    Ok(_) => {
+                        println!("Wrote data back to file ({:?})", now.elapsed());
+                        Ok(StatusCode::OK)
+                    }
+                    Err(e) => {
+                        return Err((
+                            StatusCode::INTERNAL_SERVER_ERROR,
+                            format!("Could not write file: {e}"),
+                        ))
                     }
-                }
-                Err(e) => {
-                    return Err((
-                        StatusCode::INTERNAL_SERVER_ERROR,
-                        format!("Could not serialize output to JSON string: {e}"),
-                    ))
                 }
             }
-        }
-        Err(e) => match e {
-            PartitionError::NoMap => {
-                Err((StatusCode::BAD_REQUEST, "No viable map was provided".into()))
+            Err(e) => {
+                return Err((
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    format!("Could not serialize output to JSON string: {e}"),
+                ))
             }
         },
+        Err(e) => {
+            let (status, message) = e.status();
+            Err((status, message.into()))
+        }
     };
 
     println!("Time elaposed: {:?}", now.elapsed());
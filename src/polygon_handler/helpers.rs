@@ -1,10 +1,178 @@
+use axum::http::StatusCode;
+use geo::BoundingRect;
 use local_robot_map::{
-    Algorithm, AxisResolution, LocalMap, Partition, PartitionError, PolygonMap,
-    PolygonMapError, RealWorldLocation, Visualize,
+    AxisResolution, LocalMap, LocationType, PolygonMap, PolygonMapError,
+    RealWorldLocation, Visualize,
 };
 
+use ndarray::Array2;
+
+use crate::ps::{
+    assign_frontiers, assign_targets, buffer_zone, check_consistency,
+    coverage_path, enforce_connectivity, estimate_completion, evaluate,
+    frontiers, is_assigned, join, redistribute, remark_border, robots,
+    swath_fits, Blockage, CompletionParameters, Connectivity, Context,
+    FrontierAssignment, FrontierParameters, FrontierSegment, GridFrame,
+    JoinParameters, Ownership, Scheme, TargetParameters,
+};
 use crate::{Map, RobotLocation};
 
+use super::layers;
+use super::occupancy::OccupancyGrid;
+use super::projection::{Crs, Projection};
+use super::sweep;
+use super::types::{
+    CoordXYZ, InputConsistency, InputCoverage, InputExport, InputPose,
+    InputRendezvous, OutputBorder, OutputBuffer, OutputCompletion,
+    OutputConsistency, OutputDeployment, OutputFrontier, OutputIsland,
+    OutputJoining, OutputMetrics, OutputPath, OutputRedistribution,
+    OutputRendezvous, OutputTarget, OutputZone, Report,
+};
+
+/// Reasons for which the input data could not be partitioned.
+#[derive(Debug)]
+pub(super) enum InputError {
+    /// No viable map was provided through the input polygon points.
+    NoMap,
+    /// The density does not match the map's dimensions or holds negative
+    /// values.
+    InvalidDensity,
+    /// The density image could not be read.
+    UnreadableDensity,
+    /// The terrain does not match the map's dimensions or holds costs which
+    /// are not strictly positive.
+    InvalidTerrain,
+    /// The reference system is unknown or a coordinate could not be projected.
+    InvalidCrs,
+    /// An occupancy grid was given along with a reference system other than
+    /// the local one.
+    ProjectedGrid,
+    /// The occupancy grid or its image could not be read.
+    UnreadableGrid,
+    /// The occupancy grid's data does not match its dimensions, its resolution
+    /// is not strictly positive or its origin is rotated.
+    InvalidGrid,
+    /// A robot referred to by its index does not exist.
+    UnknownRobot,
+    /// The speed or a terrain multiplier of a robot is not strictly positive.
+    InvalidFactors,
+    /// A pose along a trajectory or a footprint is not finite, or a footprint
+    /// has a negative radius or field of view.
+    InvalidTrajectory,
+    /// The DARP parameters would not let it terminate or balance the regions.
+    InvalidDarp,
+    /// The gain weight of the targets is not finite or too large, or the
+    /// auction's epsilon is not finite or negative.
+    InvalidTargets,
+    /// A coverage path was requested without its parameters.
+    NoCoverage,
+    /// The swath of a coverage path or completion estimate is narrower than
+    /// the map's cells, or the tool of a spanning tree coverage narrower than
+    /// half of them.
+    InvalidCoverage,
+    /// The given partition does not match the robots or holds cells outside
+    /// the map.
+    InvalidPartition,
+    /// A partition was given without requesting its metrics.
+    UnusedPartition,
+    /// The results to check are not in matrix coordinates or not for the same
+    /// map.
+    MismatchedResults,
+    /// The base station lies outside the map.
+    InvalidBase,
+    /// The buffer distance is negative or not finite.
+    InvalidBuffer,
+    /// A region is to be exported as occupancy grid, whose cells are square,
+    /// but the map's cells are not.
+    NonSquareCells,
+}
+
+impl InputError {
+    /// The status code and message to respond with.
+    pub(super) fn status(&self) -> (StatusCode, &'static str) {
+        match self {
+            InputError::NoMap => {
+                (StatusCode::BAD_REQUEST, "No viable map was provided")
+            }
+            InputError::InvalidDensity => (
+                StatusCode::BAD_REQUEST,
+                "Density does not match the map or is negative",
+            ),
+            InputError::UnreadableDensity => {
+                (StatusCode::BAD_REQUEST, "Density image could not be read")
+            }
+            InputError::InvalidTerrain => (
+                StatusCode::BAD_REQUEST,
+                "Terrain does not match the map or is not positive",
+            ),
+            InputError::InvalidCrs => (
+                StatusCode::BAD_REQUEST,
+                "Coordinates could not be projected",
+            ),
+            InputError::ProjectedGrid => (
+                StatusCode::BAD_REQUEST,
+                "Occupancy grids are only supported in local coordinates",
+            ),
+            InputError::UnreadableGrid => {
+                (StatusCode::BAD_REQUEST, "Occupancy grid could not be read")
+            }
+            InputError::InvalidGrid => (
+                StatusCode::BAD_REQUEST,
+                "Occupancy grid does not match its dimensions or is rotated",
+            ),
+            InputError::UnknownRobot => (
+                StatusCode::BAD_REQUEST,
+                "Robot referred to by index is unknown",
+            ),
+            InputError::InvalidFactors => (
+                StatusCode::BAD_REQUEST,
+                "Robot speed or terrain multiplier is not strictly positive",
+            ),
+            InputError::InvalidTrajectory => (
+                StatusCode::BAD_REQUEST,
+                "Trajectory or footprint is not finite",
+            ),
+            InputError::InvalidDarp => {
+                (StatusCode::BAD_REQUEST, "DARP parameters are out of range")
+            }
+            InputError::InvalidTargets => (
+                StatusCode::BAD_REQUEST,
+                "Target parameters are out of range",
+            ),
+            InputError::NoCoverage => (
+                StatusCode::BAD_REQUEST,
+                "No coverage parameters were provided",
+            ),
+            InputError::InvalidCoverage => (
+                StatusCode::BAD_REQUEST,
+                "Swath or tool is too narrow for the cells",
+            ),
+            InputError::InvalidPartition => (
+                StatusCode::BAD_REQUEST,
+                "Partition does not match the robots or the map",
+            ),
+            InputError::UnusedPartition => (
+                StatusCode::BAD_REQUEST,
+                "Partition was given without requesting metrics",
+            ),
+            InputError::InvalidBase => {
+                (StatusCode::BAD_REQUEST, "Base station lies outside the map")
+            }
+            InputError::InvalidBuffer => (
+                StatusCode::BAD_REQUEST,
+                "Buffer distance is negative or not finite",
+            ),
+            InputError::MismatchedResults => {
+                (StatusCode::BAD_REQUEST, "Results are not for the same map")
+            }
+            InputError::NonSquareCells => (
+                StatusCode::BAD_REQUEST,
+                "Occupancy grids can only be exported for square cells",
+            ),
+        }
+    }
+}
+
 pub(super) fn make_localmap(
     vertices: Vec<RealWorldLocation>,
     explored: Option<Vec<Vec<RealWorldLocation>>>,
@@ -37,22 +205,115 @@ pub(super) fn make_localmap(
 /// conversions. Additionally it will perform the partitioning and return its
 /// result.
 ///
-/// As a matter of convenience, the map is also saved to a PNG file if
-/// partitioning was successful.
+/// Any requested post-processing (e.g. connectivity enforcement) is applied
+/// to the partitioned map as well. Additional information gathered along the
+/// way is returned as a [`Report`].
+///
+/// As a matter of convenience, the map is also saved to a PNG file if it was
+/// partitioned here, i.e. no partition was uploaded.
 ///
 /// # Errors
 ///
-/// This function will return a [`PartitionError`] if the partitioning failed. A
-/// [`PolygonMapError`] can also cause an error to be returned.
+/// This function will return an [`InputError`] if the input data could not be
+/// turned into a map (e.g. due to a [`PolygonMapError`]) or is inconsistent
+/// with it.
 pub(super) fn partition_input_data(
-    data: super::types::InputData,
-    algorithm: Algorithm<Map>,
-) -> Result<Map, PartitionError> {
-    let map: Map = match make_localmap(
-        data.vertices
+    mut data: super::types::InputData,
+    algorithm: Scheme,
+) -> Result<(Map, Report), InputError> {
+    // Grids are always given in the local frame
+    if data.grid.is_some() && !matches!(data.crs, Crs::Local) {
+        return Err(InputError::ProjectedGrid);
+    }
+    let projection = match data.reference() {
+        Some(reference) => Projection::new(data.crs, reference)?,
+        // Without vertices there is no map, which is reported below
+        None => None,
+    };
+    if let Some(projection) = &projection {
+        data.project(projection)?;
+    }
+
+    let ids: Vec<Option<u64>> = std::iter::once(data.me.id)
+        .chain(data.others.iter().map(|robot| robot.id))
+        .collect();
+    let capabilities: Vec<Vec<String>> = std::iter::once(&data.me)
+        .chain(data.others.iter())
+        .map(|robot| robot.capabilities.clone())
+        .collect();
+    let trajectories: Vec<_> = std::iter::once(&mut data.me)
+        .chain(data.others.iter_mut())
+        .filter_map(|robot| {
+            let footprint = robot.footprint.take()?;
+            Some((std::mem::take(&mut robot.trajectory), footprint))
+        })
+        .collect();
+    if trajectories.iter().any(|(trajectory, footprint)| {
+        !footprint.is_valid() || !trajectory.iter().all(InputPose::is_valid)
+    }) {
+        return Err(InputError::InvalidTrajectory);
+    }
+    if std::iter::once(&data.me)
+        .chain(data.others.iter())
+        .any(|robot| matches!(&robot.factors, Some(f) if !f.is_valid()))
+    {
+        return Err(InputError::InvalidFactors);
+    }
+    if !data.darp.is_valid() {
+        return Err(InputError::InvalidDarp);
+    }
+    let requests = Requests {
+        export: data.export,
+        frontiers: data.frontiers,
+        targets: data.targets,
+        coverage: data.coverage,
+        completion: data.completion,
+        metrics: data.metrics,
+        failed: data.failed,
+        joining: data.joining,
+        connectivity: data.connectivity,
+        borders: data.borders,
+        rendezvous: data.rendezvous,
+        buffer: data.buffer,
+    };
+    requests.validate(ids.len())?;
+    let partition = data.partition;
+    if matches!(&partition, Some(p) if p.len() != ids.len()) {
+        return Err(InputError::InvalidPartition);
+    }
+    if partition.is_some() && !requests.metrics {
+        return Err(InputError::UnusedPartition);
+    }
+    let tie_break = data.tie_break;
+    let darp = data.darp;
+    let centroidal_voronoi = data.centroidal_voronoi;
+    let density = data.density;
+    let terrain = data.terrain;
+    let zones = data.zones;
+    let obstacles = data.obstacles;
+    let no_go = data.no_go;
+    let areas: Option<Vec<geo::Polygon<f64>>> = data
+        .areas
+        .map(|areas| areas.into_iter().map(|area| area.into()).collect());
+
+    let grid = data.grid.map(OccupancyGrid::load).transpose()?;
+
+    let vertices = match (&grid, &areas) {
+        (Some(grid), _) => grid.vertices(),
+        (None, Some(areas)) => bounding_box(areas),
+        (None, None) => data
+            .vertices
             .into_iter()
             .map(|v| v.into_real_world())
             .collect(),
+    };
+    let resolution = match (data.resolution, &grid) {
+        (Some(resolution), _) => resolution.into_axis_resolution(),
+        (None, Some(grid)) => grid.resolution(),
+        (None, None) => return Err(InputError::NoMap),
+    };
+    let mut map: Map = match make_localmap(
+        vertices,
         data.explored.map(|e| {
             e.into_iter()
                 .map(|polygon| {
@@ -60,22 +321,568 @@ pub(super) fn partition_input_data(
                 })
                 .collect()
         }),
-        data.resolution.into_axis_resolution(),
+        resolution,
         data.me.into(),
         data.others.into_iter().map(|v| v.into()).collect(),
     ) {
         Ok(m) => m,
         Err(e) => match e {
             PolygonMapError::NotEnoughVertices => {
-                return Err(PartitionError::NoMap)
+                return Err(InputError::NoMap)
             }
         },
     };
-    let map = map.partition(algorithm);
-    if let Ok(ref map) = map {
-        map.as_image().save("map.png").unwrap();
+    let frame = GridFrame::new(map.map());
+    requests.validate_frame(&frame)?;
+    let areas = areas.map(|areas| layers::make_areas(&areas, &frame));
+    if let Some(areas) = &areas {
+        // Cells between the areas are not to be covered
+        mark(
+            &mut map,
+            &frame,
+            areas.map(Option::is_none),
+            LocationType::OutOfMap,
+        );
+    }
+    if !trajectories.is_empty() {
+        let mut explored = sweep::sweep(trajectories, &frame);
+        // Only unexplored cells can become explored
+        explored.zip_mut_with(map.map().cells(), |explored, cell| {
+            *explored &= *cell == LocationType::Unexplored
+        });
+        mark(&mut map, &frame, explored, LocationType::Explored);
+    }
+    let zones = layers::make_zones(zones, &capabilities, &frame);
+    let mut blocked = layers::make_blocked(obstacles, no_go, &frame);
+    if let Some(grid) = &grid {
+        blocked = grid.apply(&mut map, &frame, blocked);
+    }
+    let context = Context::new(
+        ids,
+        crate::ps::robots(&map)
+            .map(|robot| robot.location())
+            .collect(),
+    )
+    .with_tie_break(tie_break)
+    .with_darp(darp)
+    .with_centroidal_voronoi(centroidal_voronoi)
+    .with_density(layers::make_density(density, &frame)?)
+    .with_terrain(layers::make_terrain(terrain, &frame)?)
+    .with_access(zones.as_ref().map(|zones| zones.access.clone()))
+    .with_blocked(blocked)
+    .with_areas(areas);
+
+    let mut report = Report {
+        blocked: context.blocked().cloned(),
+        projection,
+        ..Report::default()
+    };
+
+    // An uploaded partition replaces the scheme entirely
+    let uploaded = partition.is_some();
+    let given = partition
+        .map(|partition| uploaded_ownership(partition, &frame))
+        .transpose()?;
+    // Only computed if needed, since it partitions the map for every robot
+    let mut ownership = given.or_else(|| {
+        requests
+            .need_ownership(&context)
+            .then(|| match &requests.joining {
+                // The newcomer only takes its cells afterwards
+                Some(joining) => {
+                    let dim = (frame.rows(), frame.cols());
+                    let without = context.excluding(joining.robot, dim);
+                    Ownership::compute(&map, algorithm, &without)
+                }
+                None => Ownership::compute(&map, algorithm, &context),
+            })
+    });
+    // Frontiers are detected before the map gets partitioned
+    let segments = (requests.frontiers.is_some() || requests.targets.is_some())
+        .then(|| {
+            let parameters = requests.frontiers.unwrap_or_default();
+            frontiers(&map, &context, &parameters)
+        });
+    let mut map = match &ownership {
+        Some(ownership) if uploaded => ownership.apply(map),
+        _ => algorithm(map, &context),
+    };
+    let diagnostics = context.diagnostics();
+    report.convergence = diagnostics.convergence;
+    report.deployment = diagnostics.deployment.map(OutputDeployment::new);
+
+    if let Some(ownership) = &mut ownership {
+        map = requests.adjust(ownership, map, &context, &frame, &mut report);
+        requests.describe(ownership, &map, &context, &frame, &mut report);
+    }
+
+    if let Some(segments) = segments {
+        requests.locate_frontiers(
+            segments,
+            ownership.as_ref(),
+            &map,
+            &context,
+            &frame,
+            &mut report,
+        );
+    }
+
+    requests.plan(ownership.as_ref(), &map, &context, &frame, &mut report);
+
+    if let Some(zones) = zones {
+        report.unassignable = Some(unassignable(zones, &map, &context, &frame));
+    }
+
+    if !uploaded {
+        render(&map, context.blocked()).save("map.png").unwrap();
+    }
+    Ok((map, report))
+}
+
+/// Set the cells flagged in `cells` to `location`.
+fn mark(
+    map: &mut Map,
+    frame: &GridFrame,
+    cells: Array2<bool>,
+    location: LocationType,
+) {
+    for ((row, col), _) in cells.indexed_iter().filter(|(_, c)| **c) {
+        map.map_mut()
+            .set_location(&frame.location(row, col), location)
+            .expect("All locations are in the map");
+    }
+}
+
+/// The [`Ownership`] of an uploaded partition, holding the cells of each
+/// robot.
+///
+/// # Errors
+///
+/// This function will return [`InputError::InvalidPartition`] if a cell lies
+/// outside the map.
+fn uploaded_ownership(
+    partition: Vec<Vec<CoordXYZ>>,
+    frame: &GridFrame,
+) -> Result<Ownership, InputError> {
+    let regions = partition
+        .into_iter()
+        .map(|cells| {
+            cells
+                .into_iter()
+                .map(|cell| frame.index(&cell.into_real_world()))
+                .collect::<Option<Vec<_>>>()
+        })
+        .collect::<Option<Vec<_>>>()
+        .ok_or(InputError::InvalidPartition)?;
+    Ok(Ownership::from_regions(
+        (frame.rows(), frame.cols()),
+        &regions,
+    ))
+}
+
+/// Everything to be done with the partitioned map besides returning it.
+struct Requests {
+    export: Option<InputExport>,
+    frontiers: Option<FrontierParameters>,
+    targets: Option<TargetParameters>,
+    coverage: Option<InputCoverage>,
+    completion: Option<CompletionParameters>,
+    metrics: bool,
+    failed: Vec<usize>,
+    joining: Option<JoinParameters>,
+    connectivity: Option<Connectivity>,
+    borders: bool,
+    rendezvous: Option<InputRendezvous>,
+    buffer: Option<f64>,
+}
+
+impl Requests {
+    /// Check the parameters which do not depend on the map.
+    fn validate(&self, robots: usize) -> Result<(), InputError> {
+        if matches!(self.buffer, Some(d) if !d.is_finite() || d < 0.0) {
+            return Err(InputError::InvalidBuffer);
+        }
+        if matches!(&self.targets, Some(t) if !t.is_valid()) {
+            return Err(InputError::InvalidTargets);
+        }
+        if matches!(&self.export, Some(e) if e.robot >= robots)
+            || matches!(&self.coverage, Some(c) if c.robot >= robots)
+            || self.failed.iter().any(|&robot| robot >= robots)
+            || matches!(&self.joining, Some(j) if j.robot >= robots)
+        {
+            return Err(InputError::UnknownRobot);
+        }
+        Ok(())
+    }
+
+    /// Check the parameters which depend on the map's cells.
+    fn validate_frame(&self, frame: &GridFrame) -> Result<(), InputError> {
+        let (size_x, size_y) = frame.cell_size();
+        if self.export.is_some()
+            && (size_x - size_y).abs() > f64::EPSILON * size_x
+        {
+            return Err(InputError::NonSquareCells);
+        }
+        if matches!(&self.coverage, Some(c) if !c.planner.fits((size_x, size_y)))
+            || matches!(
+                &self.completion,
+                Some(c) if !swath_fits(c.swath, (size_x, size_y))
+            )
+        {
+            return Err(InputError::InvalidCoverage);
+        }
+        if let Some(base) = self.rendezvous.as_ref().and_then(|r| r.base) {
+            if frame.index(&base.into_real_world()).is_none() {
+                return Err(InputError::InvalidBase);
+            }
+        }
+        Ok(())
+    }
+
+    /// Whether any request needs the regions of all robots, rather than only
+    /// the one of `me`.
+    fn need_ownership(&self, context: &Context) -> bool {
+        // Describing the regions
+        let described = self.metrics
+            || self.borders
+            || self.rendezvous.is_some()
+            || self.buffer.is_some()
+            || context.has_density()
+            || context.has_areas();
+        // Planning for robots other than `me`
+        let planned = matches!(&self.export, Some(e) if e.robot != 0)
+            || matches!(&self.coverage, Some(c) if c.robot != 0)
+            || self.completion.is_some()
+            || matches!(
+                &self.frontiers,
+                Some(f) if f.assignment == FrontierAssignment::Region
+            );
+        self.adjusts() || described || planned
+    }
+
+    /// Whether any request changes the regions after partitioning.
+    fn adjusts(&self) -> bool {
+        !self.failed.is_empty()
+            || self.joining.is_some()
+            || self.connectivity.is_some()
+    }
+
+    /// Redistribute the cells of failed robots, let a robot join and enforce
+    /// connectivity, in this order. The border of my region is marked again
+    /// afterwards, if the scheme marked it.
+    fn adjust(
+        &self,
+        ownership: &mut Ownership,
+        mut map: Map,
+        context: &Context,
+        frame: &GridFrame,
+        report: &mut Report,
+    ) -> Map {
+        if !self.failed.is_empty() {
+            let (gained, stranded) =
+                redistribute(ownership, &map, context, &self.failed);
+            report.redistribution =
+                Some(OutputRedistribution::new(gained, stranded, frame));
+            map = ownership.apply(map);
+        }
+
+        if let Some(parameters) = &self.joining {
+            let (changed, lost) = join(ownership, &map, context, parameters);
+            report.joining = Some(OutputJoining { changed, lost });
+            map = ownership.apply(map);
+        }
+
+        // Handing over and taking cells may split regions, hence enforced last
+        if let Some(config) = &self.connectivity {
+            let orphans =
+                enforce_connectivity(ownership, &map, config, context);
+            report.orphans = Some(
+                orphans
+                    .into_iter()
+                    .map(|island| OutputIsland::new(island, frame))
+                    .collect(),
+            );
+            map = ownership.apply(map);
+        }
+        // The border moved along with my region
+        match context.diagnostics().mark_border {
+            Some(mark) if self.adjusts() => remark_border(map, mark),
+            _ => map,
+        }
+    }
+
+    /// Report on the regions: their borders, rendezvous points, buffer zone,
+    /// weights, areas and metrics.
+    fn describe(
+        &self,
+        ownership: &Ownership,
+        map: &Map,
+        context: &Context,
+        frame: &GridFrame,
+        report: &mut Report,
+    ) {
+        if self.borders {
+            report.borders = Some(
+                crate::ps::borders(ownership, frame)
+                    .into_iter()
+                    .map(OutputBorder::new)
+                    .collect(),
+            );
+        }
+
+        if let Some(rendezvous) = &self.rendezvous {
+            let base = rendezvous.base.map(|base| base.into_real_world());
+            let meetings =
+                crate::ps::rendezvous(map, context, ownership, base.as_ref());
+            report.rendezvous = Some(OutputRendezvous::new(meetings, frame));
+        }
+
+        if let Some(distance) = self.buffer {
+            let cells = buffer_zone(ownership, frame, context, 0, distance);
+            report.buffer = Some(OutputBuffer::new(cells, frame));
+        }
+
+        if context.has_density() {
+            report.weights = Some(ownership.weights(context));
+        }
+
+        if context.has_areas() {
+            report.areas = Some(
+                (0..context.ranks().len())
+                    .map(|robot| {
+                        let mut areas: Vec<usize> = ownership
+                            .cells_of(robot)
+                            .into_iter()
+                            .filter_map(|index| context.area(index))
+                            .collect();
+                        areas.sort_unstable();
+                        areas.dedup();
+                        areas
+                    })
+                    .collect(),
+            );
+        }
+
+        if self.metrics {
+            report.metrics = Some(OutputMetrics::new(
+                evaluate(map, context, ownership),
+                frame,
+            ));
+        }
+    }
+
+    /// Assign the detected frontiers and pick targets among them.
+    fn locate_frontiers(
+        &self,
+        mut segments: Vec<FrontierSegment>,
+        ownership: Option<&Ownership>,
+        map: &Map,
+        context: &Context,
+        frame: &GridFrame,
+        report: &mut Report,
+    ) {
+        if let Some(parameters) = &self.frontiers {
+            assign_frontiers(
+                &mut segments,
+                map,
+                context,
+                ownership,
+                parameters,
+            );
+        }
+        if let Some(parameters) = &self.targets {
+            report.targets = Some(
+                assign_targets(map, context, &segments, parameters)
+                    .into_iter()
+                    .map(|target| OutputTarget::new(target, frame))
+                    .collect(),
+            );
+        }
+        report.frontiers = Some(
+            segments
+                .into_iter()
+                .map(|segment| OutputFrontier::new(segment, frame))
+                .collect(),
+        );
+    }
+
+    /// Plan the coverage path, estimate the completion and export a region.
+    ///
+    /// Without an [`Ownership`], only the region of `me` is known.
+    fn plan(
+        &self,
+        ownership: Option<&Ownership>,
+        map: &Map,
+        context: &Context,
+        frame: &GridFrame,
+        report: &mut Report,
+    ) {
+        let region_of = |robot: usize| match ownership {
+            Some(ownership) => {
+                Array2::from_shape_fn((frame.rows(), frame.cols()), |index| {
+                    ownership.is_owned_by(index, robot)
+                })
+            }
+            None => map.map().cells().map(is_assigned),
+        };
+        if let Some(coverage) = &self.coverage {
+            let start = robots(map)
+                .nth(coverage.robot)
+                .expect("The robot exists")
+                .location();
+            let waypoints = coverage_path(
+                &coverage.planner,
+                &region_of(coverage.robot),
+                frame,
+                start,
+            );
+            report.path = Some(OutputPath::new(coverage.robot, waypoints));
+        }
+        if let Some(parameters) = &self.completion {
+            let estimates: Vec<_> = (0..robots(map).count())
+                .map(|robot| {
+                    estimate_completion(
+                        &region_of(robot),
+                        map,
+                        context,
+                        robot,
+                        parameters,
+                    )
+                })
+                .collect();
+            report.completion = Some(OutputCompletion {
+                makespan: estimates.iter().map(|e| e.time).fold(0.0, f64::max),
+                robots: estimates,
+            });
+        }
+        if let Some(export) = &self.export {
+            report.region = Some(region_of(export.robot));
+        }
+    }
+}
+
+/// The cells of each zone which none of the robots is allowed to enter.
+fn unassignable(
+    zones: layers::Zones,
+    map: &Map,
+    context: &Context,
+    frame: &GridFrame,
+) -> Vec<OutputZone> {
+    let cells = map.map().cells();
+    zones
+        .cells
+        .into_iter()
+        .map(|(name, covered)| {
+            let unassignable = covered
+                .into_iter()
+                .filter(|&(row, col)| {
+                    cells[[row, col]] == LocationType::Unexplored
+                        && context.blockage((row, col)).is_none()
+                        && !context.allows_anyone((row, col))
+                })
+                .collect();
+            OutputZone::new(name, unassignable, frame)
+        })
+        .collect()
+}
+
+/// Check whether the results which the robots computed on their own fit
+/// together.
+///
+/// # Errors
+///
+/// This function will return [`InputError::MismatchedResults`] if the results
+/// are not in matrix coordinates or not for the same map.
+pub(super) fn check_results(
+    input: InputConsistency,
+) -> Result<OutputConsistency, InputError> {
+    let first = input.results.first().ok_or(InputError::MismatchedResults)?;
+    let (rows, cols) = first
+        .cells
+        .iter()
+        .try_fold((0, 0), |(rows, cols), (coord, _)| {
+            let (row, col) = coord.matrix_index()?;
+            Some((rows.max(row + 1), cols.max(col + 1)))
+        })
+        .ok_or(InputError::MismatchedResults)?;
+    if input.results.iter().any(|result| {
+        result.offset != first.offset
+            || result.resolution != first.resolution
+            || result.cells.len() != rows * cols
+    }) {
+        return Err(InputError::MismatchedResults);
+    }
+
+    let claimed: [&str; 2] = [
+        (&LocationType::Assigned).into(),
+        (&LocationType::Frontier).into(),
+    ];
+    let unexplored: &str = (&LocationType::Unexplored).into();
+    let mut coverable = Array2::from_elem((rows, cols), false);
+    let mut claims = Vec::new();
+    for result in &input.results {
+        let mut claim = Array2::from_elem((rows, cols), false);
+        for (coord, label) in &result.cells {
+            let index = coord
+                .matrix_index()
+                .filter(|&(row, col)| row < rows && col < cols)
+                .ok_or(InputError::MismatchedResults)?;
+            if claimed.contains(&label.as_str()) {
+                claim[index] = true;
+                coverable[index] = true;
+            } else if label == unexplored {
+                coverable[index] = true;
+            }
+        }
+        claims.push(claim);
+    }
+
+    let frame = GridFrame::matrix((rows, cols));
+    let consistency =
+        check_consistency(&frame, &claims, &coverable, input.reconcile);
+    Ok(OutputConsistency::new(consistency, claims.len(), &frame))
+}
+
+/// The corners of the rectangle enclosing all areas, which is the extent of
+/// the map. Empty if there are no areas.
+fn bounding_box(areas: &[geo::Polygon<f64>]) -> Vec<RealWorldLocation> {
+    match geo::MultiPolygon::new(areas.to_vec()).bounding_rect() {
+        Some(rect) => {
+            let (min, max) = (rect.min(), rect.max());
+            vec![
+                RealWorldLocation::from_xyz(min.x, min.y, 0.0),
+                RealWorldLocation::from_xyz(max.x, min.y, 0.0),
+                RealWorldLocation::from_xyz(max.x, max.y, 0.0),
+                RealWorldLocation::from_xyz(min.x, max.y, 0.0),
+            ]
+        }
+        None => Vec::new(),
+    }
+}
+
+/// Render the map, painting obstacles black and no-go zones red.
+fn render(
+    map: &Map,
+    blocked: Option<&Array2<Option<Blockage>>>,
+) -> image::RgbImage {
+    let mut image = image::DynamicImage::from(map.as_image()).to_rgb8();
+    let Some(blocked) = blocked else {
+        return image;
+    };
+
+    // The image may be scaled with respect to the cells
+    let (rows, cols) = blocked.dim();
+    let (width, height) = image.dimensions();
+    for (x, y, pixel) in image.enumerate_pixels_mut() {
+        let row = y as usize * rows / height as usize;
+        let col = x as usize * cols / width as usize;
+        match blocked[[row, col]] {
+            Some(Blockage::Obstacle) => *pixel = image::Rgb([0, 0, 0]),
+            Some(Blockage::NoGo) => *pixel = image::Rgb([200, 0, 0]),
+            None => {}
+        }
     }
-    map
+    image
 }
 
 /// Trait for dealing with Polar coordinates given Cartesian coordinates.
@@ -2,16 +2,14 @@ use std::time::Instant;
 
 use axum::{http::StatusCode, Json};
 use geo::{ConcaveHull, ConvexHull, CoordsIter};
-use local_robot_map::{
-    Algorithm, AxisResolution, Coords, LocationType, MaskMapState, PartitionError,
-    RealWorldLocation,
-};
+use local_robot_map::{AxisResolution, Coords, LocationType, MaskMapState, RealWorldLocation};
 
 use helpers::Polar;
 
-use crate::Map;
+use crate::ps::{GridFrame, Scheme};
 
 use super::helpers;
+use super::occupancy::RegionGrid;
 use super::types;
 
 /// Partitiong a polygon map and return all cells.
@@ -26,22 +24,20 @@ use super::types;
 /// provided or if no viable map was provided through the input polygon points.
 pub async fn polygon_handler_json(
     Json(data): Json<types::InputData>,
-    algorithm: Algorithm<Map>,
+    algorithm: Scheme,
 ) -> Result<(StatusCode, Json<types::OutputData>), (StatusCode, &'static str)> {
     println!("=== Request received! ===");
     println!(">>> Partition map and return all cells");
     let now = Instant::now();
     let result = match helpers::partition_input_data(data, algorithm) {
-        Ok(map) => {
+        Ok((map, report)) => {
             println!("Partitioned map ({:?})", now.elapsed());
-            Ok((
-                StatusCode::OK,
-                Json(types::OutputData::from_cellmap(map.map())),
-            ))
+            types::OutputData::from_cellmap(map.map(), report.blocked.as_ref())
+                .with_report(report)
+                .map(|output| (StatusCode::OK, Json(output)))
+                .map_err(|e| e.status())
         }
-        Err(e) => match e {
-            PartitionError::NoMap => Err((StatusCode::BAD_REQUEST, "No viable map was provided")),
-        },
+        Err(e) => Err(e.status()),
     };
     println!("Finished processing data ({:?})", now.elapsed());
 
@@ -61,30 +57,28 @@ pub async fn polygon_handler_json(
 /// provided or if no viable map was provided through the input polygon points.
 pub async fn polygon_handler_frontiers_json(
     Json(data): Json<types::InputData>,
-    algorithm: Algorithm<Map>,
+    algorithm: Scheme,
 ) -> Result<(StatusCode, Json<types::OutputData>), (StatusCode, &'static str)> {
     println!("=== Request received! ===");
     println!(">>> Partition map and return frontier cells (edge of assigned region)");
     let now = Instant::now();
     let result = match helpers::partition_input_data(data, algorithm) {
-        Ok(map) => {
+        Ok((map, report)) => {
             println!("Partitioned map ({:?})", now.elapsed());
-            Ok((
-                StatusCode::OK,
-                Json(types::OutputData::new(
-                    map.map()
-                        .get_map_state(LocationType::Frontier)
-                        .iter()
-                        .map(|c| (c.location().into(), c.value().into()))
-                        .collect(),
-                    (&Coords::new(0.0, 0.0, 0.0)).into(),
-                    (&<AxisResolution as Default>::default()).into(),
-                )),
-            ))
+            types::OutputData::new(
+                map.map()
+                    .get_map_state(LocationType::Frontier)
+                    .iter()
+                    .map(|c| (c.location().into(), c.value().into()))
+                    .collect(),
+                (&Coords::new(0.0, 0.0, 0.0)).into(),
+                (&<AxisResolution as Default>::default()).into(),
+            )
+            .with_report(report)
+            .map(|output| (StatusCode::OK, Json(output)))
+            .map_err(|e| e.status())
         }
-        Err(e) => match e {
-            PartitionError::NoMap => Err((StatusCode::BAD_REQUEST, "No viable map was provided")),
-        },
+        Err(e) => Err(e.status()),
     };
     println!("Finished processing data ({:?})", now.elapsed());
 
@@ -114,44 +108,42 @@ pub async fn polygon_handler_frontiers_json(
 /// provided or if no viable map was provided through the input polygon points.
 pub async fn polygon_handler_contours_convex_hull(
     Json(data): Json<types::InputData>,
-    algorithm: Algorithm<Map>,
+    algorithm: Scheme,
 ) -> Result<(StatusCode, Json<types::OutputData>), (StatusCode, &'static str)> {
     println!("=== Request received! ===");
     println!(">>> Partition map and return convex hull contour cells (edge of assigned region)");
     let now = Instant::now();
     let result = match helpers::partition_input_data(data, algorithm) {
-        Ok(map) => {
+        Ok((map, report)) => {
             println!("Partitioned map ({:?})", now.elapsed());
-            Ok((
-                StatusCode::OK,
-                Json(types::OutputData::new(
-                    geo::Polygon::new(
-                        geo::LineString::from(
-                            map.map()
-                                .get_map_state(LocationType::Frontier)
-                                .iter()
-                                .map(|c| (c.location().x(), c.location().y()))
-                                .collect::<Vec<(f64, f64)>>(),
-                        ),
-                        vec![],
+            types::OutputData::new(
+                geo::Polygon::new(
+                    geo::LineString::from(
+                        map.map()
+                            .get_map_state(LocationType::Frontier)
+                            .iter()
+                            .map(|c| (c.location().x(), c.location().y()))
+                            .collect::<Vec<(f64, f64)>>(),
+                    ),
+                    vec![],
+                )
+                .convex_hull()
+                .exterior_coords_iter()
+                .map(|geo::Coord { x, y }| {
+                    (
+                        (&RealWorldLocation::new(Coords::new(x, y, 0.0))).into(),
+                        (&LocationType::Frontier).into(),
                     )
-                    .convex_hull()
-                    .exterior_coords_iter()
-                    .map(|geo::Coord { x, y }| {
-                        (
-                            (&RealWorldLocation::new(Coords::new(x, y, 0.0))).into(),
-                            (&LocationType::Frontier).into(),
-                        )
-                    })
-                    .collect(),
-                    (&Coords::new(0.0, 0.0, 0.0)).into(),
-                    (&<AxisResolution as Default>::default()).into(),
-                )),
-            ))
+                })
+                .collect(),
+                (&Coords::new(0.0, 0.0, 0.0)).into(),
+                (&<AxisResolution as Default>::default()).into(),
+            )
+            .with_report(report)
+            .map(|output| (StatusCode::OK, Json(output)))
+            .map_err(|e| e.status())
         }
-        Err(e) => match e {
-            PartitionError::NoMap => Err((StatusCode::BAD_REQUEST, "No viable map was provided")),
-        },
+        Err(e) => Err(e.status()),
     };
     println!("Finished processing data ({:?})", now.elapsed());
 
@@ -172,44 +164,42 @@ pub async fn polygon_handler_contours_convex_hull(
 /// provided or if no viable map was provided through the input polygon points.
 pub async fn polygon_handler_contours_concave_hull(
     Json(data): Json<types::InputData>,
-    algorithm: Algorithm<Map>,
+    algorithm: Scheme,
 ) -> Result<(StatusCode, Json<types::OutputData>), (StatusCode, &'static str)> {
     println!("=== Request received! ===");
     println!(">>> Partition map and return concave hull contour cells (edge of assigned region)");
     let now = Instant::now();
     let result = match helpers::partition_input_data(data, algorithm) {
-        Ok(map) => {
+        Ok((map, report)) => {
             println!("Partitioned map ({:?})", now.elapsed());
-            Ok((
-                StatusCode::OK,
-                Json(types::OutputData::new(
-                    geo::Polygon::new(
-                        geo::LineString::from(
-                            map.map()
-                                .get_map_state(LocationType::Frontier)
-                                .iter()
-                                .map(|c| (c.location().x(), c.location().y()))
-                                .collect::<Vec<(f64, f64)>>(),
-                        ),
-                        vec![],
+            types::OutputData::new(
+                geo::Polygon::new(
+                    geo::LineString::from(
+                        map.map()
+                            .get_map_state(LocationType::Frontier)
+                            .iter()
+                            .map(|c| (c.location().x(), c.location().y()))
+                            .collect::<Vec<(f64, f64)>>(),
+                    ),
+                    vec![],
+                )
+                .concave_hull(1.0)
+                .exterior_coords_iter()
+                .map(|geo::Coord { x, y }| {
+                    (
+                        (&RealWorldLocation::new(Coords::new(x, y, 0.0))).into(),
+                        (&LocationType::Frontier).into(),
                     )
-                    .concave_hull(1.0)
-                    .exterior_coords_iter()
-                    .map(|geo::Coord { x, y }| {
-                        (
-                            (&RealWorldLocation::new(Coords::new(x, y, 0.0))).into(),
-                            (&LocationType::Frontier).into(),
-                        )
-                    })
-                    .collect(),
-                    (&Coords::new(0.0, 0.0, 0.0)).into(),
-                    (&<AxisResolution as Default>::default()).into(),
-                )),
-            ))
+                })
+                .collect(),
+                (&Coords::new(0.0, 0.0, 0.0)).into(),
+                (&<AxisResolution as Default>::default()).into(),
+            )
+            .with_report(report)
+            .map(|output| (StatusCode::OK, Json(output)))
+            .map_err(|e| e.status())
         }
-        Err(e) => match e {
-            PartitionError::NoMap => Err((StatusCode::BAD_REQUEST, "No viable map was provided")),
-        },
+        Err(e) => Err(e.status()),
     };
     println!("Finished processing data ({:?})", now.elapsed());
 
@@ -234,14 +224,14 @@ pub async fn polygon_handler_contours_concave_hull(
 /// provided or if no viable map was provided through the input polygon points.
 pub async fn polygon_handler_contours_polar_angular_sort(
     Json(data): Json<types::InputData>,
-    algorithm: Algorithm<Map>,
+    algorithm: Scheme,
 ) -> Result<(StatusCode, Json<types::OutputData>), (StatusCode, &'static str)> {
     println!("=== Request received! ===");
     println!(">>> Partition map and return frontier cells (edge of assigned region)");
     let now = Instant::now();
 
     let result = match helpers::partition_input_data(data, algorithm) {
-        Ok(map) => {
+        Ok((map, report)) => {
             println!("Partitioned map ({:?})", now.elapsed());
 
             let polygon = geo::Polygon::new(
@@ -272,17 +262,17 @@ pub async fn polygon_handler_contours_polar_angular_sort(
                     });
                     println!("Sorted points ({:?})", now.elapsed());
 
-                    Ok((
-                        StatusCode::OK,
-                        Json(types::OutputData::new(
-                            points
-                                .iter()
-                                .map(|p| (p.into(), (&LocationType::Frontier).into()))
-                                .collect(),
-                            (&Coords::new(0.0, 0.0, 0.0)).into(),
-                            (&<AxisResolution as Default>::default()).into(),
-                        )),
-                    ))
+                    types::OutputData::new(
+                        points
+                            .iter()
+                            .map(|p| (p.into(), (&LocationType::Frontier).into()))
+                            .collect(),
+                        (&Coords::new(0.0, 0.0, 0.0)).into(),
+                        (&<AxisResolution as Default>::default()).into(),
+                    )
+                    .with_report(report)
+                    .map(|output| (StatusCode::OK, Json(output)))
+                    .map_err(|e| e.status())
                 }
                 None => Err((
                     StatusCode::BAD_REQUEST,
@@ -290,9 +280,7 @@ pub async fn polygon_handler_contours_polar_angular_sort(
                 )),
             }
         }
-        Err(e) => match e {
-            PartitionError::NoMap => Err((StatusCode::BAD_REQUEST, "No viable map was provided")),
-        },
+        Err(e) => Err(e.status()),
     };
     println!("Finished processing data ({:?})", now.elapsed());
 
@@ -314,14 +302,14 @@ pub async fn polygon_handler_contours_polar_angular_sort(
 /// provided or if no viable map was provided through the input polygon points.
 pub async fn polygon_handler_contours_polar_sort(
     Json(data): Json<types::InputData>,
-    algorithm: Algorithm<Map>,
+    algorithm: Scheme,
 ) -> Result<(StatusCode, Json<types::OutputData>), (StatusCode, &'static str)> {
     println!("=== Request received! ===");
     println!(">>> Partition map and return frontier cells (edge of assigned region)");
     let now = Instant::now();
 
     let result = match helpers::partition_input_data(data, algorithm) {
-        Ok(map) => {
+        Ok((map, report)) => {
             println!("Partitioned map ({:?})", now.elapsed());
 
             let polygon = geo::Polygon::new(
@@ -371,17 +359,17 @@ pub async fn polygon_handler_contours_polar_sort(
                     });
                     println!("Sorted points ({:?})", now.elapsed());
 
-                    Ok((
-                        StatusCode::OK,
-                        Json(types::OutputData::new(
-                            points
-                                .iter()
-                                .map(|p| (p.into(), (&LocationType::Frontier).into()))
-                                .collect(),
-                            (&Coords::new(0.0, 0.0, 0.0)).into(),
-                            (&<AxisResolution as Default>::default()).into(),
-                        )),
-                    ))
+                    types::OutputData::new(
+                        points
+                            .iter()
+                            .map(|p| (p.into(), (&LocationType::Frontier).into()))
+                            .collect(),
+                        (&Coords::new(0.0, 0.0, 0.0)).into(),
+                        (&<AxisResolution as Default>::default()).into(),
+                    )
+                    .with_report(report)
+                    .map(|output| (StatusCode::OK, Json(output)))
+                    .map_err(|e| e.status())
                 }
                 None => Err((
                     StatusCode::BAD_REQUEST,
@@ -389,9 +377,204 @@ pub async fn polygon_handler_contours_polar_sort(
                 )),
             }
         }
-        Err(e) => match e {
-            PartitionError::NoMap => Err((StatusCode::BAD_REQUEST, "No viable map was provided")),
-        },
+        Err(e) => Err(e.status()),
+    };
+    println!("Finished processing data ({:?})", now.elapsed());
+
+    println!("Time elapsed: {:?}", now.elapsed());
+    result
+}
+
+/// Partition a polygon map and return a robot's region as occupancy grid.
+///
+/// The region is returned as `nav_msgs/OccupancyGrid`, where all cells outside
+/// of the region are occupied. The robot is chosen through the input's
+/// `export` section, which defaults to `me`. The grid is additionally written
+/// as `map_server` PGM and YAML files if a path was provided.
+///
+/// # Errors
+///
+/// This function will return an error if the files could not be written or
+/// for the same reasons as [`polygon_handler_json`].
+pub async fn polygon_handler_occupancy_grid(
+    Json(mut data): Json<types::InputData>,
+    algorithm: Scheme,
+) -> Result<(StatusCode, Json<types::OutputOccupancyGrid>), (StatusCode, &'static str)> {
+    println!("=== Request received! ===");
+    println!(">>> Partition map and return a region as occupancy grid");
+    let now = Instant::now();
+    let export = data.export.get_or_insert_with(Default::default).clone();
+    let result = match helpers::partition_input_data(data, algorithm) {
+        Ok((map, report)) => {
+            println!("Partitioned map ({:?})", now.elapsed());
+            let mut grid = RegionGrid::new(
+                &report
+                    .region
+                    .expect("The region is provided along with an export"),
+                &GridFrame::new(map.map()),
+            );
+            if let Some(projection) = &report.projection {
+                grid.shift_to_utm(projection);
+            }
+            match export.pgm.map(|path| grid.write_files(&path)) {
+                Some(Err(_)) => Err((
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    "Could not write PGM and YAML files",
+                )),
+                _ => Ok((StatusCode::OK, Json(grid.to_message(&export.frame_id)))),
+            }
+        }
+        Err(e) => Err(e.status()),
+    };
+    println!("Finished processing data ({:?})", now.elapsed());
+
+    println!("Time elapsed: {:?}", now.elapsed());
+    result
+}
+
+/// Detect the exploration frontiers and assign a target to every robot.
+///
+/// The target assignment is configured through the input's `targets` section,
+/// which defaults to an optimal assignment using straight line distances. The
+/// frontier segments are returned as well, since the targets refer to them.
+///
+/// # Errors
+///
+/// This function will return an error for the same reasons as
+/// [`polygon_handler_json`].
+pub async fn frontier_targets_json(
+    Json(mut data): Json<types::InputData>,
+    algorithm: Scheme,
+) -> Result<(StatusCode, Json<types::OutputTargets>), (StatusCode, &'static str)> {
+    println!("=== Request received! ===");
+    println!(">>> Assign frontier targets to the robots");
+    let now = Instant::now();
+    data.targets.get_or_insert_with(Default::default);
+    let result = match helpers::partition_input_data(data, algorithm) {
+        Ok((_, mut report)) => {
+            println!("Assigned targets ({:?})", now.elapsed());
+            match report.restore_crs() {
+                Ok(_) => Ok((
+                    StatusCode::OK,
+                    Json(types::OutputTargets {
+                        targets: report.targets.unwrap_or_default(),
+                        frontiers: report.frontiers.unwrap_or_default(),
+                    }),
+                )),
+                Err(e) => Err(e.status()),
+            }
+        }
+        Err(e) => Err(e.status()),
+    };
+    println!("Finished processing data ({:?})", now.elapsed());
+
+    println!("Time elapsed: {:?}", now.elapsed());
+    result
+}
+
+/// Partition the map and plan a path covering a robot's region, as configured
+/// by the request's `coverage` section.
+pub async fn coverage_path_json(
+    Json(data): Json<types::InputData>,
+    algorithm: Scheme,
+) -> Result<(StatusCode, Json<types::OutputPath>), (StatusCode, &'static str)> {
+    println!("=== Request received! ===");
+    println!(">>> Plan a coverage path");
+    let now = Instant::now();
+    if data.coverage.is_none() {
+        return Err(helpers::InputError::NoCoverage.status());
+    }
+    let result = match helpers::partition_input_data(data, algorithm) {
+        Ok((_, mut report)) => {
+            println!("Planned path ({:?})", now.elapsed());
+            match report.restore_crs() {
+                Ok(_) => Ok((
+                    StatusCode::OK,
+                    Json(report.path.expect("A coverage path was requested")),
+                )),
+                Err(e) => Err(e.status()),
+            }
+        }
+        Err(e) => Err(e.status()),
+    };
+    println!("Finished processing data ({:?})", now.elapsed());
+
+    println!("Time elapsed: {:?}", now.elapsed());
+    result
+}
+
+/// Measure the quality of a partition, either the one computed by the given
+/// scheme or the one given by the request's `partition`.
+pub async fn evaluate_json(
+    Json(mut data): Json<types::InputData>,
+    algorithm: Scheme,
+) -> Result<(StatusCode, Json<types::OutputMetrics>), (StatusCode, &'static str)> {
+    println!("=== Request received! ===");
+    println!(">>> Evaluate a partition");
+    let now = Instant::now();
+    data.metrics = true;
+    let result = match helpers::partition_input_data(data, algorithm) {
+        Ok((_, mut report)) => {
+            println!("Evaluated partition ({:?})", now.elapsed());
+            match report.restore_crs() {
+                Ok(_) => Ok((
+                    StatusCode::OK,
+                    Json(report.metrics.expect("Metrics were requested")),
+                )),
+                Err(e) => Err(e.status()),
+            }
+        }
+        Err(e) => Err(e.status()),
+    };
+    println!("Finished processing data ({:?})", now.elapsed());
+
+    println!("Time elapsed: {:?}", now.elapsed());
+    result
+}
+
+/// Check whether the regions which the robots computed on their own overlap or
+/// leave gaps, and optionally reconcile them.
+pub async fn consistency_json(
+    Json(data): Json<types::InputConsistency>,
+) -> Result<(StatusCode, Json<types::OutputConsistency>), (StatusCode, &'static str)> {
+    println!("=== Request received! ===");
+    println!(
+        ">>> Check the consistency of {} results",
+        data.results.len()
+    );
+    let now = Instant::now();
+    let result = match helpers::check_results(data) {
+        Ok(consistency) => Ok((StatusCode::OK, Json(consistency))),
+        Err(e) => Err(e.status()),
+    };
+    println!("Finished processing data ({:?})", now.elapsed());
+
+    println!("Time elapsed: {:?}", now.elapsed());
+    result
+}
+
+/// Partition the map and propose where the robots meet, as configured by the
+/// request's `rendezvous` section.
+pub async fn rendezvous_json(
+    Json(mut data): Json<types::InputData>,
+    algorithm: Scheme,
+) -> Result<(StatusCode, Json<types::OutputRendezvous>), (StatusCode, &'static str)> {
+    println!("=== Request received! ===");
+    println!(">>> Propose rendezvous points");
+    let now = Instant::now();
+    data.rendezvous.get_or_insert_with(Default::default);
+    let result = match helpers::partition_input_data(data, algorithm) {
+        Ok((_, mut report)) => {
+            println!("Proposed rendezvous points ({:?})", now.elapsed());
+            match report.restore_crs() {
+                Ok(_) => Ok((
+                    StatusCode::OK,
+                    Json(report.rendezvous.expect("Rendezvous points were requested")),
+                )),
+                Err(e) => Err(e.status()),
+            }
+        }
+        Err(e) => Err(e.status()),
     };
     println!("Finished processing data ({:?})", now.elapsed());
 
@@ -0,0 +1,189 @@
+//! Turns the optional per-cell layers of the input data into arrays matching
+//! the map's cells.
+
+use ndarray::Array2;
+
+use crate::ps::{Blockage, GridFrame, Terrain};
+
+use super::helpers::InputError;
+use super::types::{into_polygon, CoordXYZ, InputDensity, InputTerrain, InputZone};
+
+/// Turn the density into an array matching the map's cells.
+///
+/// # Errors
+///
+/// This function will return [`InputError::InvalidDensity`] if the dimensions
+/// of a raster do not match or if any value is negative or not finite. It will
+/// return [`InputError::UnreadableDensity`] if the image could not be decoded.
+pub(super) fn make_density(
+    density: Option<InputDensity>,
+    frame: &GridFrame,
+) -> Result<Option<Array2<f64>>, InputError> {
+    let shape = (frame.rows(), frame.cols());
+    let density = match density {
+        None => return Ok(None),
+        Some(InputDensity::Raster(rows)) => {
+            if rows.len() != shape.0 || rows.iter().any(|row| row.len() != shape.1) {
+                return Err(InputError::InvalidDensity);
+            }
+            Array2::from_shape_vec(shape, rows.into_iter().flatten().collect())
+                .expect("Dimensions were checked")
+        }
+        Some(InputDensity::Image { image, scale }) => {
+            let image = match image::load_from_memory(&image) {
+                Ok(i) => i.to_luma8(),
+                Err(_) => return Err(InputError::UnreadableDensity),
+            };
+            let image = image::imageops::resize(
+                &image,
+                shape.1 as u32,
+                shape.0 as u32,
+                image::imageops::FilterType::Triangle,
+            );
+            // The first row of pixels is the top of the map, whereas the map's
+            // rows may run either way
+            let upwards =
+                frame.location(shape.0.saturating_sub(1), 0).y() > frame.location(0, 0).y();
+            Array2::from_shape_fn(shape, |(row, col)| {
+                let row = if upwards { shape.0 - 1 - row } else { row };
+                image.get_pixel(col as u32, row as u32)[0] as f64 / 255.0 * scale
+            })
+        }
+        Some(InputDensity::Polygons { polygons, default }) => {
+            let mut density = Array2::from_elem(shape, default);
+            for polygon in polygons {
+                let weight = polygon.weight;
+                for (row, col) in frame.covered_by(&into_polygon(polygon.vertices)) {
+                    density[[row, col]] = weight;
+                }
+            }
+            density
+        }
+    };
+
+    if density
+        .iter()
+        .any(|value| !value.is_finite() || *value < 0.0)
+    {
+        return Err(InputError::InvalidDensity);
+    }
+    Ok(Some(density))
+}
+
+/// Turn the terrain into a [`Terrain`] matching the map's cells.
+///
+/// # Errors
+///
+/// This function will return [`InputError::InvalidTerrain`] if the dimensions
+/// of a raster do not match or if any cost is not strictly positive.
+pub(super) fn make_terrain(
+    terrain: Option<InputTerrain>,
+    frame: &GridFrame,
+) -> Result<Option<Terrain>, InputError> {
+    let shape = (frame.rows(), frame.cols());
+    let mut classes = Array2::from_elem(shape, None);
+    let mut names = Vec::new();
+    let costs = match terrain {
+        None => return Ok(None),
+        Some(InputTerrain::Raster(rows)) => {
+            if rows.len() != shape.0 || rows.iter().any(|row| row.len() != shape.1) {
+                return Err(InputError::InvalidTerrain);
+            }
+            Array2::from_shape_vec(shape, rows.into_iter().flatten().collect())
+                .expect("Dimensions were checked")
+        }
+        Some(InputTerrain::Polygons { polygons, default }) => {
+            let mut costs = Array2::from_elem(shape, default);
+            for polygon in polygons {
+                let class = match names.iter().position(|name| name == &polygon.class) {
+                    Some(class) => class,
+                    None => {
+                        names.push(polygon.class);
+                        names.len() - 1
+                    }
+                };
+                for (row, col) in frame.covered_by(&into_polygon(polygon.vertices)) {
+                    costs[[row, col]] = polygon.cost;
+                    classes[[row, col]] = Some(class);
+                }
+            }
+            costs
+        }
+    };
+
+    if costs.iter().any(|cost| !cost.is_finite() || *cost <= 0.0) {
+        return Err(InputError::InvalidTerrain);
+    }
+    Ok(Some(Terrain::new(costs, classes, names)))
+}
+
+/// The cells covered by each zone along with which robots may enter them.
+pub(super) struct Zones {
+    /// For every robot, whether it may enter each cell.
+    pub(super) access: Vec<Array2<bool>>,
+    /// The name and cells of each zone.
+    pub(super) cells: Vec<(String, Vec<(usize, usize)>)>,
+}
+
+/// Rasterize the zones and decide which robots may enter them, given the
+/// `capabilities` of each robot.
+pub(super) fn make_zones(
+    zones: Option<Vec<InputZone>>,
+    capabilities: &[Vec<String>],
+    frame: &GridFrame,
+) -> Option<Zones> {
+    let shape = (frame.rows(), frame.cols());
+    let mut access = vec![Array2::from_elem(shape, true); capabilities.len()];
+    let mut cells = Vec::new();
+
+    for zone in zones? {
+        let covered = frame.covered_by(&into_polygon(zone.vertices));
+        for (robot, tags) in capabilities.iter().enumerate() {
+            if tags.iter().any(|tag| zone.allowed.contains(tag)) {
+                continue;
+            }
+            for &(row, col) in &covered {
+                access[robot][[row, col]] = false;
+            }
+        }
+        cells.push((zone.name, covered));
+    }
+
+    Some(Zones { access, cells })
+}
+
+/// Rasterize the obstacles and no-go zones. Obstacles take precedence where
+/// both overlap.
+pub(super) fn make_blocked(
+    obstacles: Option<Vec<Vec<CoordXYZ>>>,
+    no_go: Option<Vec<Vec<CoordXYZ>>>,
+    frame: &GridFrame,
+) -> Option<Array2<Option<Blockage>>> {
+    if obstacles.is_none() && no_go.is_none() {
+        return None;
+    }
+
+    let mut blocked = Array2::from_elem((frame.rows(), frame.cols()), None);
+    let layers = [(no_go, Blockage::NoGo), (obstacles, Blockage::Obstacle)];
+    for (polygons, blockage) in layers {
+        for polygon in polygons.into_iter().flatten() {
+            for (row, col) in frame.covered_by(&into_polygon(polygon)) {
+                blocked[[row, col]] = Some(blockage);
+            }
+        }
+    }
+
+    Some(blocked)
+}
+
+/// Rasterize the areas of the map, giving the index of the area each cell lies
+/// in. Later areas take precedence where they overlap.
+pub(super) fn make_areas(areas: &[geo::Polygon<f64>], frame: &GridFrame) -> Array2<Option<usize>> {
+    let mut cells = Array2::from_elem((frame.rows(), frame.cols()), None);
+    for (area, polygon) in areas.iter().enumerate() {
+        for (row, col) in frame.covered_by(polygon) {
+            cells[[row, col]] = Some(area);
+        }
+    }
+    cells
+}
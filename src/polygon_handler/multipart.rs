@@ -0,0 +1,106 @@
+use std::time::Instant;
+
+use axum::extract::Multipart;
+use axum::{http::StatusCode, Json};
+
+use crate::ps::Scheme;
+
+use super::helpers;
+use super::types;
+
+const MALFORMED: (StatusCode, &str) = (StatusCode::BAD_REQUEST, "Malformed multipart request");
+
+/// Partition an uploaded occupancy grid and return all cells.
+///
+/// The request consists of the following parts:
+/// - `data`: the JSON input data, just like [`super::polygon_handler_json`]
+/// - `yaml`: the `map_server` YAML file
+/// - `image`: the image the YAML file refers to (e.g. `map.pgm`)
+/// - `free` (optional): what free cells stand for, i.e. `unexplored` or
+///   `explored`
+/// - `density` (optional): a grayscale image of the importance of the cells,
+///   see [`types::InputDensity::Image`]
+/// - `density_scale` (optional): the importance of white pixels, `1` if omitted
+///
+/// The grid may be omitted, in which case the map is taken from `data`.
+///
+/// # Errors
+///
+/// This function will return an error if the request is malformed, if only
+/// one of the YAML file and the image was provided or for the same reasons as
+/// [`super::polygon_handler_json`].
+pub async fn polygon_handler_multipart(
+    mut multipart: Multipart,
+    algorithm: Scheme,
+) -> Result<(StatusCode, Json<types::OutputData>), (StatusCode, &'static str)> {
+    println!("=== Request received! ===");
+    println!(">>> Partition uploaded map and return all cells");
+    let now = Instant::now();
+
+    let mut data: Option<types::InputData> = None;
+    let (mut yaml, mut image) = (None, None);
+    let mut free = types::FreeCells::default();
+    let (mut density, mut scale) = (None, types::default_scale());
+    while let Some(field) = multipart.next_field().await.map_err(|_| MALFORMED)? {
+        let name = field.name().unwrap_or_default().to_owned();
+        let bytes = field.bytes().await.map_err(|_| MALFORMED)?;
+        match name.as_str() {
+            "data" => match serde_json::from_slice(&bytes) {
+                Ok(d) => data = Some(d),
+                Err(_) => return Err((StatusCode::BAD_REQUEST, "Could not convert to JSON")),
+            },
+            "yaml" => yaml = Some(bytes.to_vec()),
+            "image" => image = Some(bytes.to_vec()),
+            "density" => density = Some(bytes.to_vec()),
+            "density_scale" => match String::from_utf8_lossy(&bytes).trim().parse() {
+                Ok(s) => scale = s,
+                Err(_) => return Err((StatusCode::BAD_REQUEST, "Invalid density scale")),
+            },
+            "free" => {
+                let policy = String::from_utf8_lossy(&bytes).trim().to_owned();
+                match serde_json::from_value(serde_json::Value::String(policy)) {
+                    Ok(f) => free = f,
+                    Err(_) => return Err((StatusCode::BAD_REQUEST, "Unknown free cells policy")),
+                }
+            }
+            _ => {}
+        }
+    }
+    println!("Received all parts ({:?})", now.elapsed());
+
+    let Some(mut data) = data else {
+        return Err((StatusCode::BAD_REQUEST, "Missing data part"));
+    };
+    match (yaml, image) {
+        (Some(yaml), Some(image)) => {
+            data.grid = Some(types::InputGrid {
+                source: types::InputGridSource::Upload { yaml, image },
+                free,
+            })
+        }
+        (None, None) => {}
+        _ => {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                "Both the YAML file and the image are required",
+            ))
+        }
+    }
+    if let Some(image) = density {
+        data.density = Some(types::InputDensity::Image { image, scale });
+    }
+
+    let result = match helpers::partition_input_data(data, algorithm) {
+        Ok((map, report)) => {
+            println!("Partitioned map ({:?})", now.elapsed());
+            types::OutputData::from_cellmap(map.map(), report.blocked.as_ref())
+                .with_report(report)
+                .map(|output| (StatusCode::OK, Json(output)))
+                .map_err(|e| e.status())
+        }
+        Err(e) => Err(e.status()),
+    };
+
+    println!("Time elapsed: {:?}", now.elapsed());
+    result
+}
@@ -0,0 +1,383 @@
+//! Reads occupancy grids as produced by ROS `map_server` (a PGM image along
+//! with a YAML file) or published as `nav_msgs/OccupancyGrid`, and writes a
+//! robot's region in the same formats.
+//!
+//! The grid's origin is its lower left corner, i.e. the first row lies at the
+//! lowest y coordinate. Rotated origins (a non-zero yaw) are not supported and
+//! hence rejected.
+
+use std::path::Path;
+
+use local_robot_map::{AxisResolution, LocationType, RealWorldLocation};
+use ndarray::Array2;
+use serde::{Deserialize, Serialize};
+
+use crate::ps::{Blockage, GridFrame};
+use crate::Map;
+
+use super::helpers::InputError;
+use super::projection::Projection;
+use super::types::{
+    FreeCells, InputGrid, InputGridSource, OutputGridHeader, OutputGridInfo, OutputOccupancyGrid,
+};
+
+/// The occupancy thresholds used if none are provided, as in `map_server`.
+const OCCUPIED_THRESH: f64 = 0.65;
+const FREE_THRESH: f64 = 0.196;
+
+/// The state of a cell of the occupancy grid.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Occupancy {
+    Free,
+    Occupied,
+    Unknown,
+}
+
+/// The `map_server` YAML file.
+#[derive(Deserialize, Serialize, Debug)]
+struct MapMetadata {
+    image: String,
+    resolution: f64,
+    origin: [f64; 3],
+    #[serde(default)]
+    negate: u8,
+    #[serde(default = "default_occupied_thresh")]
+    occupied_thresh: f64,
+    #[serde(default = "default_free_thresh")]
+    free_thresh: f64,
+}
+
+pub(super) fn default_occupied_thresh() -> f64 {
+    OCCUPIED_THRESH
+}
+
+pub(super) fn default_free_thresh() -> f64 {
+    FREE_THRESH
+}
+
+/// An occupancy grid in the map's frame.
+pub(super) struct OccupancyGrid {
+    cells: Array2<Occupancy>,
+    resolution: f64,
+    origin: (f64, f64),
+    free: FreeCells,
+}
+
+impl OccupancyGrid {
+    /// Read the grid from any of its sources.
+    ///
+    /// # Errors
+    ///
+    /// This function will return [`InputError::UnreadableGrid`] if the files
+    /// could not be read or parsed and [`InputError::InvalidGrid`] if the grid
+    /// is inconsistent (e.g. its data does not match its dimensions) or
+    /// rotated.
+    pub(super) fn load(grid: InputGrid) -> Result<Self, InputError> {
+        let grid = match grid.source {
+            InputGridSource::File { yaml } => {
+                let contents = std::fs::read(&yaml).map_err(|_| InputError::UnreadableGrid)?;
+                let directory = Path::new(&yaml).parent().unwrap_or(Path::new(""));
+                let (metadata, image) = parse_metadata(&contents, |image| {
+                    std::fs::read(directory.join(image)).map_err(|_| InputError::UnreadableGrid)
+                })?;
+                Self::from_image(&metadata, &image, grid.free)?
+            }
+            InputGridSource::Upload { yaml, image } => {
+                let (metadata, image) = parse_metadata(&yaml, |_| Ok(image))?;
+                Self::from_image(&metadata, &image, grid.free)?
+            }
+            InputGridSource::Inline { info, data } => {
+                let shape = (info.height, info.width);
+                let origin = info.origin.into_real_world();
+                if shape.0.checked_mul(shape.1) != Some(data.len()) {
+                    return Err(InputError::InvalidGrid);
+                }
+                let cells = Array2::from_shape_vec(shape, data)
+                    .expect("Dimensions were checked")
+                    .mapv(|value| {
+                        if value < 0 {
+                            Occupancy::Unknown
+                        } else {
+                            let occupancy = value as f64 / 100.0;
+                            classify(occupancy, info.occupied_thresh, info.free_thresh)
+                        }
+                    });
+                Self {
+                    cells,
+                    resolution: info.resolution,
+                    origin: (origin.x(), origin.y()),
+                    free: grid.free,
+                }
+            }
+        };
+
+        if !grid.resolution.is_finite() || grid.resolution <= 0.0 || grid.cells.is_empty() {
+            return Err(InputError::InvalidGrid);
+        }
+        Ok(grid)
+    }
+
+    fn from_image(
+        metadata: &MapMetadata,
+        image: &[u8],
+        free: FreeCells,
+    ) -> Result<Self, InputError> {
+        if metadata.origin[2] != 0.0 {
+            return Err(InputError::InvalidGrid);
+        }
+        let image = image::load_from_memory(image)
+            .map_err(|_| InputError::UnreadableGrid)?
+            .to_luma8();
+        let (width, height) = image.dimensions();
+
+        // The first row of pixels is the top of the map
+        let cells = Array2::from_shape_fn((height as usize, width as usize), |(row, col)| {
+            let value = image.get_pixel(col as u32, height - 1 - row as u32)[0] as f64 / 255.0;
+            let occupancy = if metadata.negate == 0 {
+                1.0 - value
+            } else {
+                value
+            };
+            classify(occupancy, metadata.occupied_thresh, metadata.free_thresh)
+        });
+
+        Ok(Self {
+            cells,
+            resolution: metadata.resolution,
+            origin: (metadata.origin[0], metadata.origin[1]),
+            free,
+        })
+    }
+
+    /// The corners of the grid, which is the extent of the map.
+    pub(super) fn vertices(&self) -> Vec<RealWorldLocation> {
+        let (rows, cols) = self.cells.dim();
+        let (x, y) = self.origin;
+        let (width, height) = (cols as f64 * self.resolution, rows as f64 * self.resolution);
+        vec![
+            RealWorldLocation::from_xyz(x, y, 0.0),
+            RealWorldLocation::from_xyz(x + width, y, 0.0),
+            RealWorldLocation::from_xyz(x + width, y + height, 0.0),
+            RealWorldLocation::from_xyz(x, y + height, 0.0),
+        ]
+    }
+
+    pub(super) fn resolution(&self) -> AxisResolution {
+        AxisResolution::new(self.resolution, self.resolution, 1.0)
+    }
+
+    /// Transfer the grid onto the map. Every cell of the map which overlaps an
+    /// occupied cell becomes an obstacle, while free cells are handled
+    /// according to the [`FreeCells`] policy. Unknown cells are left
+    /// unexplored.
+    pub(super) fn apply(
+        &self,
+        map: &mut Map,
+        frame: &GridFrame,
+        blocked: Option<Array2<Option<Blockage>>>,
+    ) -> Option<Array2<Option<Blockage>>> {
+        let mut blocked =
+            blocked.unwrap_or_else(|| Array2::from_elem((frame.rows(), frame.cols()), None));
+
+        for ((row, col), occupancy) in self.cells.indexed_iter() {
+            let (x, y) = (
+                self.origin.0 + col as f64 * self.resolution,
+                self.origin.1 + row as f64 * self.resolution,
+            );
+            let footprint = geo::Rect::new(
+                geo::coord! { x: x, y: y },
+                geo::coord! { x: x + self.resolution, y: y + self.resolution },
+            );
+            for (row, col) in frame.overlapping(&footprint) {
+                match (occupancy, self.free) {
+                    (Occupancy::Occupied, _) => blocked[[row, col]] = Some(Blockage::Obstacle),
+                    (Occupancy::Free, FreeCells::Explored) => map
+                        .map_mut()
+                        .set_location(&frame.location(row, col), LocationType::Explored)
+                        .expect("All locations are in the map"),
+                    _ => {}
+                }
+            }
+        }
+
+        Some(blocked)
+    }
+}
+
+/// A robot's region as an occupancy grid, where every cell outside of the
+/// region is occupied. This allows keep-out filters to use it as is.
+pub(super) struct RegionGrid {
+    /// Whether each cell is free, the first row being the bottom of the map.
+    free: Array2<bool>,
+    resolution: f64,
+    origin: (f64, f64),
+}
+
+impl RegionGrid {
+    /// Creates a new [`RegionGrid`] from the cells of the `region`. The cells
+    /// are expected to be square.
+    pub(super) fn new(region: &Array2<bool>, frame: &GridFrame) -> Self {
+        let (size_x, size_y) = frame.cell_size();
+        let (first, last) = (frame.location(0, 0), frame.location(frame.rows() - 1, 0));
+        let mut free = region.clone();
+        // The map's rows may run from the top to the bottom
+        if last.y() < first.y() {
+            free.invert_axis(ndarray::Axis(0));
+        }
+
+        Self {
+            free,
+            resolution: size_x,
+            origin: (
+                first.x().min(last.x()) - size_x / 2.0,
+                first.y().min(last.y()) - size_y / 2.0,
+            ),
+        }
+    }
+
+    /// Move the grid's origin from the local frame to UTM, as is done for
+    /// matrix output (see [`super::types::OutputData::with_report`]).
+    pub(super) fn shift_to_utm(&mut self, projection: &Projection) {
+        let mut origin = (self.origin.0, self.origin.1, 0.0);
+        projection.to_utm(&mut origin);
+        self.origin = (origin.0, origin.1);
+    }
+
+    /// The grid as a `nav_msgs/OccupancyGrid` message.
+    pub(super) fn to_message(&self, frame_id: &str) -> OutputOccupancyGrid {
+        let (rows, cols) = self.free.dim();
+        OutputOccupancyGrid {
+            header: OutputGridHeader {
+                frame_id: frame_id.to_owned(),
+            },
+            info: OutputGridInfo::new(self.resolution, cols, rows, self.origin),
+            data: self
+                .free
+                .iter()
+                .map(|&free| if free { 0 } else { 100 })
+                .collect(),
+        }
+    }
+
+    /// Write the grid as `<path>.pgm` and `<path>.yaml`, as expected by
+    /// `map_server`.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if any of the files could not be
+    /// written.
+    pub(super) fn write_files(&self, path: &str) -> std::io::Result<()> {
+        let (rows, cols) = self.free.dim();
+        let image = format!("{path}.pgm");
+
+        // The first row of pixels is the top of the map
+        let mut pgm = format!("P5\n{cols} {rows}\n255\n").into_bytes();
+        for row in self.free.outer_iter().rev() {
+            pgm.extend(row.iter().map(|&free| if free { 254u8 } else { 0 }));
+        }
+        std::fs::write(&image, pgm)?;
+
+        let metadata = MapMetadata {
+            image: Path::new(&image)
+                .file_name()
+                .map_or(image.clone(), |name| name.to_string_lossy().into_owned()),
+            resolution: self.resolution,
+            origin: [self.origin.0, self.origin.1, 0.0],
+            negate: 0,
+            occupied_thresh: OCCUPIED_THRESH,
+            free_thresh: FREE_THRESH,
+        };
+        let yaml = serde_yaml::to_string(&metadata).expect("The metadata can always be serialized");
+        std::fs::write(format!("{path}.yaml"), yaml)
+    }
+}
+
+/// Read the YAML file and the image it refers to.
+fn parse_metadata(
+    yaml: &[u8],
+    read_image: impl FnOnce(&str) -> Result<Vec<u8>, InputError>,
+) -> Result<(MapMetadata, Vec<u8>), InputError> {
+    let metadata: MapMetadata =
+        serde_yaml::from_slice(yaml).map_err(|_| InputError::UnreadableGrid)?;
+    let image = read_image(&metadata.image)?;
+    Ok((metadata, image))
+}
+
+fn classify(occupancy: f64, occupied_thresh: f64, free_thresh: f64) -> Occupancy {
+    if occupancy > occupied_thresh {
+        Occupancy::Occupied
+    } else if occupancy < free_thresh {
+        Occupancy::Free
+    } else {
+        Occupancy::Unknown
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn inline(width: usize, height: usize, data: &[i8]) -> Result<OccupancyGrid, InputError> {
+        let grid = serde_json::json!({
+            "info": {
+                "resolution": 0.5,
+                "width": width,
+                "height": height,
+                "origin": { "x": 1.0, "y": 2.0, "z": 0.0 },
+            },
+            "data": data,
+        });
+        OccupancyGrid::load(serde_json::from_value(grid).expect("The grid is valid JSON"))
+    }
+
+    fn upload(yaw: f64) -> Result<OccupancyGrid, InputError> {
+        let yaml = format!("image: map.pgm\nresolution: 0.5\norigin: [1.0, 2.0, {yaw}]\n");
+        // Two by two pixels, the top left one being occupied
+        let mut image = b"P5\n2 2\n255\n".to_vec();
+        image.extend([0, 254, 254, 254]);
+        OccupancyGrid::load(InputGrid {
+            source: InputGridSource::Upload {
+                yaml: yaml.into_bytes(),
+                image,
+            },
+            free: FreeCells::default(),
+        })
+    }
+
+    #[test]
+    fn inline_rows_start_at_the_bottom() {
+        let grid = inline(3, 2, &[100, 0, -1, 0, 0, 0]).expect("The grid is consistent");
+
+        assert_eq!(grid.cells.dim(), (2, 3));
+        assert_eq!(grid.cells[[0, 0]], Occupancy::Occupied);
+        assert_eq!(grid.cells[[0, 2]], Occupancy::Unknown);
+        assert_eq!(grid.cells[[1, 0]], Occupancy::Free);
+        assert_eq!(grid.origin, (1.0, 2.0));
+    }
+
+    #[test]
+    fn image_rows_start_at_the_top() {
+        let grid = upload(0.0).expect("The grid is consistent");
+
+        assert_eq!(grid.cells[[1, 0]], Occupancy::Occupied);
+        assert_eq!(grid.cells[[0, 0]], Occupancy::Free);
+        let corners: Vec<(f64, f64)> = grid.vertices().iter().map(|v| (v.x(), v.y())).collect();
+        assert_eq!(
+            corners,
+            vec![(1.0, 2.0), (2.0, 2.0), (2.0, 3.0), (1.0, 3.0)]
+        );
+    }
+
+    #[test]
+    fn inconsistent_grids_are_rejected() {
+        assert!(matches!(upload(0.5), Err(InputError::InvalidGrid)));
+        assert!(matches!(
+            inline(3, 3, &[0; 6]),
+            Err(InputError::InvalidGrid)
+        ));
+        assert!(matches!(
+            inline(usize::MAX, 2, &[0; 2]),
+            Err(InputError::InvalidGrid)
+        ));
+    }
+}
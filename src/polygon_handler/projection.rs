@@ -0,0 +1,187 @@
+//! Projects geographic input coordinates into a local metric frame and back.
+//!
+//! The map is always rasterized in metres. Geographic (WGS84) coordinates are
+//! hence projected into a UTM zone, using [`proj4rs`] which works entirely
+//! offline. UTM coordinates, whether given or projected, are then shifted into
+//! the local frame.
+//!
+//! Both the zone and the local frame's origin are taken from the lower corner
+//! of the map's bounding box. Since every robot sends the same map, all of
+//! them end up in the same frame and hence on the same grid.
+
+use proj4rs::proj::Proj;
+use serde::Deserialize;
+
+use super::helpers::InputError;
+
+/// The coordinate reference system of the input (and output) coordinates.
+#[derive(Deserialize, Debug, Default, Clone, Copy)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub(crate) enum Crs {
+    /// Local metric coordinates.
+    #[default]
+    Local,
+    /// Longitude (x) and latitude (y) in degrees, altitude (z) in metres.
+    Wgs84,
+    /// Easting (x) and northing (y) within a UTM zone in metres. The zone is
+    /// only validated, since the coordinates never leave it.
+    Utm { zone: u8 },
+}
+
+/// Converts between the input's reference system and the local frame.
+pub(crate) struct Projection {
+    /// The geographic and UTM systems, if the input is geographic.
+    utm: Option<(Proj, Proj)>,
+    /// The UTM coordinates of the local frame's origin.
+    origin: (f64, f64),
+}
+
+impl Projection {
+    /// Creates the [`Projection`] for the `crs`, if any is required. The local
+    /// frame's origin is the `reference` (x, y), which also determines the UTM
+    /// zone for WGS84 coordinates.
+    ///
+    /// # Errors
+    ///
+    /// This function will return [`InputError::InvalidCrs`] if the UTM zone
+    /// does not exist or the reference is not a valid coordinate.
+    pub(super) fn new(crs: Crs, (x, y): (f64, f64)) -> Result<Option<Self>, InputError> {
+        match crs {
+            // Already metric and local, hence used as is
+            Crs::Local => Ok(None),
+            Crs::Utm { zone } if (1..=60).contains(&zone) && x.is_finite() && y.is_finite() => {
+                Ok(Some(Self {
+                    utm: None,
+                    origin: (x, y),
+                }))
+            }
+            Crs::Utm { .. } => Err(InputError::InvalidCrs),
+            Crs::Wgs84 => {
+                let (lon, lat) = (x, y);
+                if !(-180.0..=180.0).contains(&lon) || !(-80.0..=84.0).contains(&lat) {
+                    return Err(InputError::InvalidCrs);
+                }
+                let zone = (((lon + 180.0) / 6.0).floor() as u8).min(59) + 1;
+                let south = if lat < 0.0 { " +south" } else { "" };
+                let geographic = Proj::from_proj_string("+proj=longlat +ellps=WGS84")
+                    .map_err(|_| InputError::InvalidCrs)?;
+                let local = Proj::from_proj_string(&format!(
+                    "+proj=utm +zone={zone}{south} +ellps=WGS84 +units=m"
+                ))
+                .map_err(|_| InputError::InvalidCrs)?;
+
+                let mut origin = (lon.to_radians(), lat.to_radians(), 0.0);
+                proj4rs::transform::transform(&geographic, &local, &mut origin)
+                    .map_err(|_| InputError::InvalidCrs)?;
+                Ok(Some(Self {
+                    utm: Some((geographic, local)),
+                    origin: (origin.0, origin.1),
+                }))
+            }
+        }
+    }
+
+    /// Project a coordinate of the input's reference system into the local
+    /// frame.
+    ///
+    /// # Errors
+    ///
+    /// This function will return [`InputError::InvalidCrs`] if the coordinate
+    /// could not be projected.
+    pub(super) fn forward(&self, point: &mut (f64, f64, f64)) -> Result<(), InputError> {
+        if let Some((geographic, local)) = &self.utm {
+            let mut projected = (point.0.to_radians(), point.1.to_radians(), point.2);
+            proj4rs::transform::transform(geographic, local, &mut projected)
+                .map_err(|_| InputError::InvalidCrs)?;
+            *point = projected;
+        }
+        point.0 -= self.origin.0;
+        point.1 -= self.origin.1;
+        Ok(())
+    }
+
+    /// Convert a coordinate of the local frame back to the input's reference
+    /// system.
+    ///
+    /// # Errors
+    ///
+    /// This function will return [`InputError::InvalidCrs`] if the coordinate
+    /// lies outside of the UTM zone's domain.
+    pub(super) fn inverse(&self, point: &mut (f64, f64, f64)) -> Result<(), InputError> {
+        self.to_utm(point);
+        if let Some((geographic, local)) = &self.utm {
+            proj4rs::transform::transform(local, geographic, point)
+                .map_err(|_| InputError::InvalidCrs)?;
+            point.0 = point.0.to_degrees();
+            point.1 = point.1.to_degrees();
+        }
+        Ok(())
+    }
+
+    /// Convert a coordinate of the local frame to UTM, which keeps it metric.
+    pub(super) fn to_utm(&self, point: &mut (f64, f64, f64)) {
+        point.0 += self.origin.0;
+        point.1 += self.origin.1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn projection(crs: Crs, reference: (f64, f64)) -> Projection {
+        Projection::new(crs, reference)
+            .expect("The reference is valid")
+            .expect("The reference system is not local")
+    }
+
+    #[test]
+    fn geographic_round_trip() {
+        let projection = projection(Crs::Wgs84, (8.54, 47.37));
+        let mut origin = (8.54, 47.37, 0.0);
+        let mut point = (8.55, 47.38, 12.0);
+
+        projection
+            .forward(&mut origin)
+            .expect("The origin projects");
+        projection.forward(&mut point).expect("The point projects");
+        assert!(origin.0.abs() < 1e-6 && origin.1.abs() < 1e-6);
+        // Roughly 750 m east and 1100 m north of the origin
+        assert!((700.0..800.0).contains(&point.0));
+        assert!((1050.0..1150.0).contains(&point.1));
+
+        projection
+            .inverse(&mut point)
+            .expect("The point converts back");
+        assert!((point.0 - 8.55).abs() < 1e-9 && (point.1 - 47.38).abs() < 1e-9);
+        assert_eq!(point.2, 12.0);
+    }
+
+    #[test]
+    fn utm_is_shifted_only() {
+        let projection = projection(Crs::Utm { zone: 32 }, (465_000.0, 5_245_000.0));
+        let mut point = (465_010.0, 5_245_020.0, 0.0);
+
+        projection
+            .forward(&mut point)
+            .expect("UTM coordinates are shifted");
+        assert_eq!((point.0, point.1), (10.0, 20.0));
+        projection
+            .inverse(&mut point)
+            .expect("UTM coordinates are shifted");
+        assert_eq!((point.0, point.1), (465_010.0, 5_245_020.0));
+    }
+
+    #[test]
+    fn invalid_references_are_rejected() {
+        assert!(matches!(
+            Projection::new(Crs::Utm { zone: 61 }, (0.0, 0.0)),
+            Err(InputError::InvalidCrs)
+        ));
+        assert!(matches!(
+            Projection::new(Crs::Wgs84, (0.0, 85.0)),
+            Err(InputError::InvalidCrs)
+        ));
+        assert!(matches!(Projection::new(Crs::Local, (0.0, 0.0)), Ok(None)));
+    }
+}
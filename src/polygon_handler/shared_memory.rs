@@ -1,9 +1,8 @@
 use std::time::Instant;
 
 use axum::{http::StatusCode, Json};
-use local_robot_map::{Algorithm, PartitionError};
 
-use crate::Map;
+use crate::ps::Scheme;
 
 use super::helpers;
 use super::types;
@@ -22,7 +21,7 @@ use super::types;
 /// provided or if no viable map was provided through the input polygon points.
 pub async fn polygon_handler_shm(
     Json(data): Json<types::InputData>,
-    algorithm: Algorithm<Map>,
+    algorithm: Scheme,
 ) -> Result<StatusCode, (StatusCode, &'static str)> {
     #![allow(unreachable_code, unused_variables)]
     println!("=== Request received! ===");
@@ -35,13 +34,13 @@ pub async fn polygon_handler_shm(
     let now = Instant::now();
 
     let result = match helpers::partition_input_data(data, algorithm) {
-        Ok(map) => {
-            let output = Json(types::OutputData::from_cellmap(map.map()));
+        Ok((map, report)) => {
+            let output = types::OutputData::from_cellmap(map.map(), report.blocked.as_ref())
+                .with_report(report)
+                .map(Json);
             Ok(StatusCode::OK)
         }
-        Err(e) => match e {
-            PartitionError::NoMap => Err((StatusCode::BAD_REQUEST, "No viable map was provided")),
-        },
+        Err(e) => Err(e.status()),
     };
 
     println!("Time elaposed: {:?}", now.elapsed());
@@ -0,0 +1,262 @@
+//! Marks the area covered by the robots' sensors along their trajectories.
+//!
+//! Between two poses the robot is assumed to move straight while turning
+//! steadily. The area swept by the footprint in the meantime is rasterised
+//! piece by piece: every edge of the footprint sweeps a quadrilateral between
+//! two consecutive intermediate poses, which only need to be close enough to
+//! follow the turn. A cell is explored once its location lies inside any of
+//! these pieces or a placed footprint, just like [`GridFrame::covered_by`].
+//!
+//! Only the part of a trajectory from which the footprint can reach the map is
+//! considered, hence the work does not depend on how far the poses are apart.
+
+use std::f64::consts::{PI, TAU};
+
+use geo::{ConvexHull, MapCoords};
+use ndarray::Array2;
+
+use crate::ps::GridFrame;
+
+use super::types::{into_polygon, InputFootprint, InputPose};
+
+/// The number of segments used to approximate a full circle, and likewise the
+/// number of intermediate poses of a full turn.
+const CIRCLE_SEGMENTS: usize = 72;
+
+/// A pose in the local frame, i.e. `(x, y, heading)`.
+type Pose = (f64, f64, f64);
+
+/// The cells covered by the footprint of each robot along its trajectory.
+///
+/// All poses and footprints must be finite (see [`InputPose::is_valid`] and
+/// [`InputFootprint::is_valid`]).
+pub(super) fn sweep(
+    robots: Vec<(Vec<InputPose>, InputFootprint)>,
+    frame: &GridFrame,
+) -> Array2<bool> {
+    let mut explored = Array2::from_elem((frame.rows(), frame.cols()), false);
+    let bounds = frame.bounds();
+
+    for (mut trajectory, footprint) in robots {
+        trajectory.sort_by(|a, b| a.time.total_cmp(&b.time));
+        let poses: Vec<Pose> = trajectory
+            .into_iter()
+            .map(|pose| {
+                let location = pose.position.into_real_world();
+                (location.x(), location.y(), pose.heading)
+            })
+            .collect();
+        let shape = footprint_shape(footprint);
+        let reach = shape
+            .exterior()
+            .points()
+            .map(|p| p.x().hypot(p.y()))
+            .fold(0.0, f64::max);
+        // The robot has to be in here for its footprint to reach any cell
+        let area = geo::Rect::new(
+            (bounds.min().x - reach, bounds.min().y - reach),
+            (bounds.max().x + reach, bounds.max().y + reach),
+        );
+
+        // A single pose is a segment without movement
+        let segments = poses
+            .windows(2)
+            .map(|pair| (pair[0], pair[1]))
+            .chain((poses.len() == 1).then(|| (poses[0], poses[0])));
+        for (a, b) in segments {
+            let Some((a, b)) = clip(a, b, &area) else {
+                continue;
+            };
+            for piece in swept(&shape, a, b) {
+                for (row, col) in frame.covered_by(&piece) {
+                    explored[[row, col]] = true;
+                }
+            }
+        }
+    }
+
+    explored
+}
+
+/// The footprint as polygon relative to the robot, heading along the x axis.
+fn footprint_shape(footprint: InputFootprint) -> geo::Polygon<f64> {
+    match footprint {
+        InputFootprint::Polygon { vertices } => into_polygon(vertices),
+        InputFootprint::Circle { radius, fov } => {
+            let fov = fov.unwrap_or(TAU).clamp(0.0, TAU);
+            let segments = ((CIRCLE_SEGMENTS as f64 * fov / TAU).ceil() as usize).max(1);
+            let arc = (0..=segments).map(|i| {
+                let angle = -fov / 2.0 + fov * i as f64 / segments as f64;
+                (radius * angle.cos(), radius * angle.sin())
+            });
+            // A sector also includes the robot itself
+            let vertices: Vec<(f64, f64)> = if fov < TAU {
+                std::iter::once((0.0, 0.0)).chain(arc).collect()
+            } else {
+                arc.collect()
+            };
+            geo::Polygon::new(vertices.into(), vec![])
+        }
+    }
+}
+
+/// Move the footprint to the pose.
+fn place(shape: &geo::Polygon<f64>, (x, y, heading): Pose) -> geo::Polygon<f64> {
+    let (sin, cos) = heading.sin_cos();
+    shape.map_coords(|geo::Coord { x: dx, y: dy }| geo::Coord {
+        x: x + dx * cos - dy * sin,
+        y: y + dx * sin + dy * cos,
+    })
+}
+
+/// The part of the segment between the poses `a` and `b` which lies inside
+/// `area`, if any. The heading is turned the shortest way around.
+fn clip(a: Pose, b: Pose, area: &geo::Rect<f64>) -> Option<(Pose, Pose)> {
+    let turn = (b.2 - a.2 + PI).rem_euclid(TAU) - PI;
+    let (dx, dy) = (b.0 - a.0, b.1 - a.1);
+    let (min, max) = (area.min(), area.max());
+
+    // Narrow the segment down to the inside of each side. The points on a
+    // side are derived from the side itself, since the segment's parameter
+    // is too coarse to locate them for far away poses.
+    let heading = |t: f64| a.2 + t * turn;
+    let mut first = (0.0, a);
+    let mut last = (1.0, (b.0, b.1, heading(1.0)));
+    for (delta, distance, bound, vertical) in [
+        (-dx, a.0 - min.x, min.x, true),
+        (dx, max.x - a.0, max.x, true),
+        (-dy, a.1 - min.y, min.y, false),
+        (dy, max.y - a.1, max.y, false),
+    ] {
+        if delta == 0.0 {
+            if distance < 0.0 {
+                return None;
+            }
+            continue;
+        }
+        let t = distance / delta;
+        let point = if vertical {
+            (bound, a.1 + (bound - a.0) * (dy / dx), heading(t))
+        } else {
+            (a.0 + (bound - a.1) * (dx / dy), bound, heading(t))
+        };
+        if delta < 0.0 && t > first.0 {
+            first = (t, point);
+        } else if delta > 0.0 && t < last.0 {
+            last = (t, point);
+        }
+    }
+    if first.0 > last.0 {
+        return None;
+    }
+
+    let inside = |(x, y, heading): Pose| (x.clamp(min.x, max.x), y.clamp(min.y, max.y), heading);
+    Some((inside(first.1), inside(last.1)))
+}
+
+/// The pieces of the area swept by the footprint while moving from the pose
+/// `a` to `b`: the footprint at either end and the quadrilateral swept by each
+/// of its edges between consecutive intermediate poses.
+fn swept(shape: &geo::Polygon<f64>, a: Pose, b: Pose) -> Vec<geo::Polygon<f64>> {
+    let turn = b.2 - a.2;
+    let count = ((turn.abs() / TAU * CIRCLE_SEGMENTS as f64).ceil() as usize).max(1);
+    let poses: Vec<Pose> = (0..=count)
+        .map(|i| {
+            let t = i as f64 / count as f64;
+            (a.0 + t * (b.0 - a.0), a.1 + t * (b.1 - a.1), a.2 + t * turn)
+        })
+        .collect();
+    let placed: Vec<geo::Polygon<f64>> = poses.iter().map(|&pose| place(shape, pose)).collect();
+
+    let mut pieces = vec![placed[0].clone(), placed[count].clone()];
+    for pair in placed.windows(2) {
+        let (from, to) = (pair[0].exterior(), pair[1].exterior());
+        pieces.extend(from.lines().zip(to.lines()).map(|(from, to)| {
+            geo::MultiPoint::from(vec![from.start, from.end, to.start, to.end]).convex_hull()
+        }));
+    }
+    pieces
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn robot(poses: &[Pose], footprint: serde_json::Value) -> (Vec<InputPose>, InputFootprint) {
+        let poses: Vec<serde_json::Value> = poses
+            .iter()
+            .enumerate()
+            .map(|(time, &(x, y, heading))| {
+                serde_json::json!({
+                    "time": time,
+                    "position": { "x": x, "y": y, "z": 0.0 },
+                    "heading": heading,
+                })
+            })
+            .collect();
+        serde_json::from_value(serde_json::json!([poses, footprint]))
+            .expect("The trajectory is valid")
+    }
+
+    fn line(from: (f64, f64), to: (f64, f64)) -> serde_json::Value {
+        serde_json::json!({ "vertices": [
+            { "x": from.0, "y": from.1, "z": 0.0 },
+            { "x": to.0, "y": to.1, "z": 0.0 },
+        ]})
+    }
+
+    fn explored_cells(explored: &Array2<bool>) -> Vec<(usize, usize)> {
+        explored
+            .indexed_iter()
+            .filter(|(_, explored)| **explored)
+            .map(|(index, _)| index)
+            .collect()
+    }
+
+    #[test]
+    fn thin_footprints_sweep_every_cell() {
+        // A line across the heading, ahead of the robot by less than a cell
+        let footprint = line((0.3, -1.2), (0.3, 1.2));
+        let frame = GridFrame::matrix((10, 10));
+
+        let explored = sweep(
+            vec![robot(&[(0.0, 5.0, 0.0), (9.0, 5.0, 0.0)], footprint)],
+            &frame,
+        );
+
+        let expected: Vec<_> = (4..=6)
+            .flat_map(|row| (1..10).map(move |col| (row, col)))
+            .collect();
+        assert_eq!(explored_cells(&explored), expected);
+    }
+
+    #[test]
+    fn turning_sweeps_a_sector() {
+        let footprint = line((0.0, 0.0), (2.5, 0.0));
+        let frame = GridFrame::matrix((10, 10));
+        let turn = [(5.0, 5.0, -0.1), (5.0, 5.0, PI / 2.0 + 0.1)];
+
+        let mut explored = sweep(vec![robot(&turn, footprint)], &frame);
+
+        // The robot's own cell merely touches the pieces
+        explored[[5, 5]] = false;
+        let expected: Vec<_> = (5..8)
+            .flat_map(|row| (5..8).map(move |col| (row, col)))
+            .filter(|&index| index != (5, 5))
+            .filter(|&(row, col): &(usize, usize)| (row - 5).pow(2) + (col - 5).pow(2) < 7)
+            .collect();
+        assert_eq!(explored_cells(&explored), expected);
+    }
+
+    #[test]
+    fn far_away_poses_are_clipped() {
+        let footprint = serde_json::json!({ "radius": 0.6 });
+        let frame = GridFrame::matrix((10, 10));
+        let poses = [(-1e300, 5.0, 0.0), (1e300, 5.0, 0.0), (1e300, 1e300, 0.0)];
+
+        let explored = sweep(vec![robot(&poses, footprint)], &frame);
+
+        let expected: Vec<_> = (0..10).map(|col| (5, col)).collect();
+        assert_eq!(explored_cells(&explored), expected);
+    }
+}
@@ -1,9 +1,19 @@
-use local_robot_map::{AxisResolution, CellMap, Coords, RealWorldLocation};
+use local_robot_map::{AxisResolution, CellMap, Coords, LocationType, RealWorldLocation};
 use serde::{Deserialize, Serialize};
 
-use crate::ps::Factors;
+use ndarray::Array2;
 
-#[derive(Deserialize, Serialize, Debug)]
+use super::helpers::InputError;
+use super::projection::{Crs, Projection};
+
+use crate::ps::{
+    BaseVisit, Blockage, Border, CentroidalVoronoiParameters, Completion, CompletionParameters,
+    Conflict, Connectivity, Consistency, Convergence, CoveragePlanner, DarpParameters, Deployment,
+    Factors, FrontierParameters, FrontierSegment, GridFrame, Handover, Island, JoinParameters,
+    Meetings, PartitionMetrics, RegionMetrics, Target, TargetParameters, TieBreak,
+};
+
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq)]
 pub(crate) struct CoordXYZ {
     x: f64,
     y: f64,
@@ -17,6 +27,31 @@ impl CoordXYZ {
     pub(super) fn into_axis_resolution(self) -> AxisResolution {
         AxisResolution::new(self.x, self.y, self.z)
     }
+    /// The (`row`, `col`) index of matrix coordinates, if they are any.
+    pub(super) fn matrix_index(&self) -> Option<(usize, usize)> {
+        let valid = |v: f64| v >= 0.0 && v.fract() == 0.0;
+        (valid(self.x) && valid(self.y)).then_some((self.y as usize, self.x as usize))
+    }
+    fn is_finite(&self) -> bool {
+        self.x.is_finite() && self.y.is_finite() && self.z.is_finite()
+    }
+    fn project(&mut self, projection: &Projection) -> Result<(), InputError> {
+        let mut point = (self.x, self.y, self.z);
+        projection.forward(&mut point)?;
+        (self.x, self.y, self.z) = point;
+        Ok(())
+    }
+    fn unproject(&mut self, projection: &Projection) -> Result<(), InputError> {
+        let mut point = (self.x, self.y, self.z);
+        projection.inverse(&mut point)?;
+        (self.x, self.y, self.z) = point;
+        Ok(())
+    }
+    fn shift_to_utm(&mut self, projection: &Projection) {
+        let mut point = (self.x, self.y, self.z);
+        projection.to_utm(&mut point);
+        (self.x, self.y, self.z) = point;
+    }
 }
 
 impl From<&Coords> for CoordXYZ {
@@ -51,8 +86,62 @@ impl From<&AxisResolution> for CoordXYZ {
 
 #[derive(Deserialize, Debug)]
 pub(crate) struct InputRobot {
+    /// Used to rank the robots when settling ties; must be the same in every
+    /// robot's request.
+    pub(crate) id: Option<u64>,
     pub(crate) position: CoordXYZ,
     pub(crate) factors: Option<Factors>,
+    /// Tags which grant access to restricted zones, e.g. `"aerial"`.
+    #[serde(default)]
+    pub(crate) capabilities: Vec<String>,
+    /// The poses the robot went through so far. Only used along with a
+    /// footprint, whose area is then marked as explored.
+    #[serde(default)]
+    pub(crate) trajectory: Vec<InputPose>,
+    pub(crate) footprint: Option<InputFootprint>,
+}
+
+/// A pose along a robot's trajectory.
+#[derive(Deserialize, Debug)]
+pub(crate) struct InputPose {
+    /// Used to order the poses, e.g. in seconds.
+    pub(crate) time: f64,
+    pub(crate) position: CoordXYZ,
+    /// Counter-clockwise from the x axis in radians.
+    #[serde(default)]
+    pub(crate) heading: f64,
+}
+
+/// The area a robot's sensor covers.
+#[derive(Deserialize, Debug)]
+#[serde(untagged)]
+pub(crate) enum InputFootprint {
+    /// A circle around the robot, optionally limited to a field of view (in
+    /// radians) centred on the heading.
+    Circle { radius: f64, fov: Option<f64> },
+    /// A polygon relative to the robot, the heading pointing along the x axis.
+    Polygon { vertices: Vec<CoordXYZ> },
+}
+
+impl InputPose {
+    /// Whether all of the pose's values are finite.
+    pub(crate) fn is_valid(&self) -> bool {
+        self.time.is_finite() && self.heading.is_finite() && self.position.is_finite()
+    }
+}
+
+impl InputFootprint {
+    /// Whether the footprint is finite, i.e. a circle's radius is finite and
+    /// not negative and so is its field of view, if any.
+    pub(crate) fn is_valid(&self) -> bool {
+        match self {
+            InputFootprint::Circle { radius, fov } => {
+                let valid = |value: &f64| value.is_finite() && *value >= 0.0;
+                valid(radius) && fov.iter().all(valid)
+            }
+            InputFootprint::Polygon { vertices } => vertices.iter().all(CoordXYZ::is_finite),
+        }
+    }
 }
 
 impl From<InputRobot> for crate::RobotLocation {
@@ -63,11 +152,341 @@ impl From<InputRobot> for crate::RobotLocation {
 
 #[derive(Deserialize, Debug)]
 pub struct InputData {
+    /// The reference system of all coordinates, except for the resolution
+    /// which is always in metres.
+    #[serde(default)]
+    pub(crate) crs: Crs,
+    /// Ignored if `areas` are provided.
+    #[serde(default)]
     pub(crate) vertices: Vec<CoordXYZ>,
+    /// Several disjoint areas making up the map, in place of `vertices`.
+    pub(crate) areas: Option<Vec<InputArea>>,
     pub(crate) explored: Option<Vec<Vec<CoordXYZ>>>,
-    pub(crate) resolution: CoordXYZ,
+    /// Required unless a grid is provided, whose resolution is used otherwise.
+    pub(crate) resolution: Option<CoordXYZ>,
     pub(crate) me: InputRobot,
     pub(crate) others: Vec<InputRobot>,
+    #[serde(default)]
+    pub(crate) tie_break: TieBreak,
+    /// Enforce every region to be connected to its robot, if provided.
+    pub(crate) connectivity: Option<Connectivity>,
+    /// Only used by the DARP scheme.
+    #[serde(default)]
+    pub(crate) darp: DarpParameters,
+    /// Only used by the centroidal Voronoi scheme.
+    #[serde(default)]
+    pub(crate) centroidal_voronoi: CentroidalVoronoiParameters,
+    /// The importance of each cell. All cells are equally important if
+    /// omitted. Only DARP balances the regions on it and the centroidal
+    /// Voronoi scheme weighs its centroids by it, while all other schemes
+    /// ignore it. The resulting weights are reported nonetheless.
+    pub(crate) density: Option<InputDensity>,
+    /// The cost of traversing each cell. All cells cost `1` if omitted.
+    pub(crate) terrain: Option<InputTerrain>,
+    /// Zones which only some robots may enter.
+    pub(crate) zones: Option<Vec<InputZone>>,
+    /// Polygons which cannot be entered, e.g. buildings.
+    pub(crate) obstacles: Option<Vec<Vec<CoordXYZ>>>,
+    /// Polygons which must not be entered, e.g. restricted airspace.
+    pub(crate) no_go: Option<Vec<Vec<CoordXYZ>>>,
+    /// An occupancy grid used as the map, in place of `vertices` and `areas`.
+    /// It is always in local metric coordinates.
+    pub(crate) grid: Option<InputGrid>,
+    /// Which region to export as occupancy grid, if any.
+    pub(crate) export: Option<InputExport>,
+    /// Detect exploration frontiers and assign them to the robots, if
+    /// provided.
+    pub(crate) frontiers: Option<FrontierParameters>,
+    /// Assign a frontier segment as target to each robot, if provided.
+    pub(crate) targets: Option<TargetParameters>,
+    /// Plan a path covering a robot's region, if provided.
+    pub(crate) coverage: Option<InputCoverage>,
+    /// Estimate how long each robot needs to cover its region, if provided.
+    pub(crate) completion: Option<CompletionParameters>,
+    /// The cells of each robot's region in real-world coordinates, `me` first
+    /// followed by `others` in order. Used in place of the computed partition,
+    /// hence no scheme runs at all. Only accepted along with `metrics`.
+    pub(crate) partition: Option<Vec<Vec<CoordXYZ>>>,
+    /// Measure the quality of the partition.
+    #[serde(default)]
+    pub(crate) metrics: bool,
+    /// Robots which stopped working (by index, `0` being `me` followed by
+    /// `others` in order). Their regions are handed over to their neighbours.
+    #[serde(default)]
+    pub(crate) failed: Vec<usize>,
+    /// A robot which joins, taking cells from nearby robots only.
+    pub(crate) joining: Option<JoinParameters>,
+    /// Report the borders between neighbouring robots.
+    #[serde(default)]
+    pub(crate) borders: bool,
+    /// Propose where the robots meet, if provided.
+    pub(crate) rendezvous: Option<InputRendezvous>,
+    /// Extend my region by this distance (in metres) into the regions of my
+    /// neighbours, if provided. The distance must not be negative.
+    pub(crate) buffer: Option<f64>,
+}
+
+impl InputData {
+    /// Project all coordinates into the local frame.
+    ///
+    /// # Errors
+    ///
+    /// This function will return [`InputError::InvalidCrs`] if any coordinate
+    /// could not be projected.
+    pub(super) fn project(&mut self, projection: &Projection) -> Result<(), InputError> {
+        let mut coords: Vec<&mut CoordXYZ> = Vec::new();
+        coords.extend(self.vertices.iter_mut());
+        for area in self.areas.iter_mut().flatten() {
+            coords.extend(area.vertices.iter_mut());
+            coords.extend(area.holes.iter_mut().flatten());
+        }
+        coords.extend(self.explored.iter_mut().flatten().flatten());
+        for robot in std::iter::once(&mut self.me).chain(self.others.iter_mut()) {
+            coords.push(&mut robot.position);
+            coords.extend(robot.trajectory.iter_mut().map(|pose| &mut pose.position));
+        }
+        for zone in self.zones.iter_mut().flatten() {
+            coords.extend(zone.vertices.iter_mut());
+        }
+        if let Some(InputDensity::Polygons { polygons, .. }) = &mut self.density {
+            coords.extend(polygons.iter_mut().flat_map(|p| p.vertices.iter_mut()));
+        }
+        if let Some(InputTerrain::Polygons { polygons, .. }) = &mut self.terrain {
+            coords.extend(polygons.iter_mut().flat_map(|p| p.vertices.iter_mut()));
+        }
+        coords.extend(self.obstacles.iter_mut().flatten().flatten());
+        coords.extend(self.no_go.iter_mut().flatten().flatten());
+        coords.extend(self.partition.iter_mut().flatten().flatten());
+        if let Some(rendezvous) = &mut self.rendezvous {
+            coords.extend(rendezvous.base.as_mut());
+        }
+
+        coords
+            .into_iter()
+            .try_for_each(|coord| coord.project(projection))
+    }
+
+    /// The lower corner of the map's bounding box, which determines the local
+    /// frame. Unlike the robots' positions, every robot shares the map and
+    /// hence the frame. `None` if the map has no vertices, e.g. for a grid.
+    pub(super) fn reference(&self) -> Option<(f64, f64)> {
+        let vertices: Vec<&CoordXYZ> = match &self.areas {
+            Some(areas) => areas.iter().flat_map(|area| &area.vertices).collect(),
+            None => self.vertices.iter().collect(),
+        };
+        vertices
+            .into_iter()
+            .map(|vertex| (vertex.x, vertex.y))
+            .reduce(|(min_x, min_y), (x, y)| (min_x.min(x), min_y.min(y)))
+    }
+}
+
+/// One of the areas of operation, e.g. a field or a building wing.
+#[derive(Deserialize, Debug)]
+pub(crate) struct InputArea {
+    pub(crate) vertices: Vec<CoordXYZ>,
+    #[serde(default)]
+    pub(crate) holes: Vec<Vec<CoordXYZ>>,
+}
+
+impl From<InputArea> for geo::Polygon<f64> {
+    fn from(value: InputArea) -> Self {
+        geo::Polygon::new(
+            into_polygon(value.vertices).into_inner().0,
+            value
+                .holes
+                .into_iter()
+                .map(|hole| into_polygon(hole).into_inner().0)
+                .collect(),
+        )
+    }
+}
+
+/// An occupancy grid along with how to treat its free cells.
+#[derive(Deserialize, Debug)]
+pub(crate) struct InputGrid {
+    #[serde(flatten)]
+    pub(crate) source: InputGridSource,
+    #[serde(default)]
+    pub(crate) free: FreeCells,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(untagged)]
+pub(crate) enum InputGridSource {
+    /// Path to the `map_server` YAML file. The image path is relative to it.
+    File { yaml: String },
+    /// A `nav_msgs/OccupancyGrid`, where `data` holds the occupancy of each
+    /// cell in percent (`-1` being unknown) in row-major order.
+    Inline { info: InputGridInfo, data: Vec<i8> },
+    /// The contents of the YAML file and the image, e.g. from a multipart
+    /// upload.
+    #[serde(skip)]
+    Upload { yaml: Vec<u8>, image: Vec<u8> },
+}
+
+#[derive(Deserialize, Debug)]
+pub(crate) struct InputGridInfo {
+    pub(crate) resolution: f64,
+    pub(crate) width: usize,
+    pub(crate) height: usize,
+    /// The lower left corner of the grid.
+    pub(crate) origin: CoordXYZ,
+    #[serde(default = "super::occupancy::default_occupied_thresh")]
+    pub(crate) occupied_thresh: f64,
+    #[serde(default = "super::occupancy::default_free_thresh")]
+    pub(crate) free_thresh: f64,
+}
+
+/// How to export a robot's region as occupancy grid.
+#[derive(Deserialize, Debug, Clone)]
+pub(crate) struct InputExport {
+    /// Index of the robot, `0` being `me` followed by `others` in order.
+    #[serde(default)]
+    pub(crate) robot: usize,
+    #[serde(default = "default_frame_id")]
+    pub(crate) frame_id: String,
+    /// Also write the grid to `<pgm>.pgm` and `<pgm>.yaml`, if provided.
+    pub(crate) pgm: Option<String>,
+}
+
+impl Default for InputExport {
+    fn default() -> Self {
+        Self {
+            robot: 0,
+            frame_id: default_frame_id(),
+            pgm: None,
+        }
+    }
+}
+
+/// Where the robots are to meet.
+#[derive(Deserialize, Debug, Default)]
+pub(crate) struct InputRendezvous {
+    /// The position of a stationary base station, which every robot also
+    /// meets at.
+    pub(crate) base: Option<CoordXYZ>,
+}
+
+/// Which robot's region to plan a coverage path for, and how.
+#[derive(Deserialize, Debug, Clone, Copy)]
+pub(crate) struct InputCoverage {
+    /// Index of the robot, `0` being `me` followed by `others` in order.
+    #[serde(default)]
+    pub(crate) robot: usize,
+    #[serde(flatten)]
+    pub(crate) planner: CoveragePlanner,
+}
+
+/// The results which the robots computed on their own, to be checked for
+/// consistency.
+#[derive(Deserialize, Debug)]
+pub struct InputConsistency {
+    /// The responses of an endpoint with matrix coordinates, e.g.
+    /// `/PolygonToCellMap`, for the same map.
+    pub(crate) results: Vec<InputResult>,
+    /// Also return a partition without overlaps and gaps.
+    #[serde(default)]
+    pub(crate) reconcile: bool,
+}
+
+/// A previous response holding a single robot's region.
+#[derive(Deserialize, Debug)]
+pub(crate) struct InputResult {
+    pub(crate) cells: Vec<(CoordXYZ, String)>,
+    pub(crate) offset: CoordXYZ,
+    pub(crate) resolution: CoordXYZ,
+}
+
+fn default_frame_id() -> String {
+    "map".to_owned()
+}
+
+/// What free cells of an occupancy grid stand for.
+#[derive(Deserialize, Debug, Default, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum FreeCells {
+    /// Free cells still need to be covered.
+    #[default]
+    Unexplored,
+    /// Free cells have already been covered (e.g. by the SLAM run).
+    Explored,
+}
+
+/// A zone which only robots with certain capabilities may enter.
+#[derive(Deserialize, Debug)]
+pub(crate) struct InputZone {
+    pub(crate) name: String,
+    pub(crate) vertices: Vec<CoordXYZ>,
+    /// Robots having any of these capabilities may enter the zone.
+    pub(crate) allowed: Vec<String>,
+}
+
+/// The importance of each cell of the map.
+#[derive(Deserialize, Debug)]
+#[serde(untagged)]
+pub(crate) enum InputDensity {
+    /// Indexed like the output cells, i.e. `density[row][col]`.
+    Raster(Vec<Vec<f64>>),
+    /// A grayscale image which is stretched over the map. The first row of
+    /// pixels is the top of the map (as for occupancy grids). Black is `0` and
+    /// white is `scale`. Only uploaded along with a multipart request.
+    #[serde(skip)]
+    Image { image: Vec<u8>, scale: f64 },
+    /// Cells inside a polygon take its weight; the last matching polygon wins.
+    /// All remaining cells take the `default` weight.
+    Polygons {
+        polygons: Vec<InputWeightedPolygon>,
+        #[serde(default = "default_scale")]
+        default: f64,
+    },
+}
+
+/// The cost of traversing each cell of the map. A cost of `2` means that it
+/// takes twice as long to cross a cell than usual.
+#[derive(Deserialize, Debug)]
+#[serde(untagged)]
+pub(crate) enum InputTerrain {
+    /// Indexed like the output cells, i.e. `terrain[row][col]`.
+    Raster(Vec<Vec<f64>>),
+    /// Cells inside a polygon belong to its terrain class; the last matching
+    /// polygon wins. All remaining cells cost `default`.
+    Polygons {
+        polygons: Vec<InputTerrainPolygon>,
+        #[serde(default = "default_scale")]
+        default: f64,
+    },
+}
+
+#[derive(Deserialize, Debug)]
+pub(crate) struct InputTerrainPolygon {
+    pub(crate) vertices: Vec<CoordXYZ>,
+    /// The name of the terrain class, which robots can refer to in their
+    /// factors.
+    pub(crate) class: String,
+    pub(crate) cost: f64,
+}
+
+pub(super) fn default_scale() -> f64 {
+    1.0
+}
+
+#[derive(Deserialize, Debug)]
+pub(crate) struct InputWeightedPolygon {
+    pub(crate) vertices: Vec<CoordXYZ>,
+    pub(crate) weight: f64,
+}
+
+/// Turn the vertices into a polygon, ignoring the 3rd dimension.
+pub(crate) fn into_polygon(vertices: Vec<CoordXYZ>) -> geo::Polygon<f64> {
+    geo::Polygon::new(
+        vertices
+            .into_iter()
+            .map(|v| (v.x, v.y))
+            .collect::<Vec<(f64, f64)>>()
+            .into(),
+        vec![],
+    )
 }
 
 #[derive(Serialize)]
@@ -75,10 +494,563 @@ pub struct OutputData {
     cells: Vec<(CoordXYZ, &'static str)>,
     offset: CoordXYZ,
     resolution: CoordXYZ,
+    #[serde(flatten)]
+    report: Report,
+    /// Whether the `cells` are in matrix rather than real-world coordinates.
+    #[serde(skip)]
+    matrix: bool,
+}
+
+/// Additional information gathered while partitioning. Only the sections which
+/// were requested are part of the output.
+#[derive(Serialize, Default)]
+pub struct Report {
+    /// Islands owned by nobody after enforcing connectivity.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(super) orphans: Option<Vec<OutputIsland>>,
+    /// How an iterative scheme converged.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(super) convergence: Option<Convergence>,
+    /// Where the robots should be deployed.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(super) deployment: Option<OutputDeployment>,
+    /// The total importance of each robot's region, `me` first followed by
+    /// `others` in order. Only provided along with a density.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(super) weights: Option<Vec<f64>>,
+    /// Cells of each zone which none of the robots may enter. Only provided
+    /// along with zones.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(super) unassignable: Option<Vec<OutputZone>>,
+    /// The areas (by index) each robot's region lies in, `me` first followed
+    /// by `others` in order. Only provided along with areas.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(super) areas: Option<Vec<Vec<usize>>>,
+    /// Cells which are obstacles or no-go zones. Not serialized on its own,
+    /// but used to label the output cells (see [`OutputData::from_cellmap`]).
+    #[serde(skip)]
+    pub(super) blocked: Option<Array2<Option<Blockage>>>,
+    /// The exploration frontiers and the robot each is assigned to.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(super) frontiers: Option<Vec<OutputFrontier>>,
+    /// The target of each robot, `me` first followed by `others` in order.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(super) targets: Option<Vec<OutputTarget>>,
+    /// The coverage path of the chosen robot.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(super) path: Option<OutputPath>,
+    /// How long the robots need to cover their regions.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(super) completion: Option<OutputCompletion>,
+    /// The quality of the partition.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(super) metrics: Option<OutputMetrics>,
+    /// How the regions of failed robots were handed over.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(super) redistribution: Option<OutputRedistribution>,
+    /// How a joining robot took its cells.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(super) joining: Option<OutputJoining>,
+    /// The border of every pair of neighbouring robots.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(super) borders: Option<Vec<OutputBorder>>,
+    /// Where the robots meet.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(super) rendezvous: Option<OutputRendezvous>,
+    /// The cells of my neighbours which extend my region. Not serialized on its
+    /// own, but used to label the output cells (see [`OutputData::with_report`]).
+    #[serde(skip)]
+    pub(super) buffer: Option<OutputBuffer>,
+    /// The cells of the robot chosen for export. Only provided along with an
+    /// export.
+    #[serde(skip)]
+    pub(super) region: Option<Array2<bool>>,
+    /// Converts the output back to the input's reference system, if needed.
+    #[serde(skip)]
+    pub(super) projection: Option<Projection>,
+}
+
+impl Report {
+    /// Convert all coordinates back to the input's reference system. Returns
+    /// the projection for converting further coordinates, if any is needed.
+    ///
+    /// # Errors
+    ///
+    /// This function will return [`InputError::InvalidCrs`] if any coordinate
+    /// could not be converted.
+    pub(super) fn restore_crs(&mut self) -> Result<Option<Projection>, InputError> {
+        let Some(projection) = self.projection.take() else {
+            return Ok(None);
+        };
+        self.unproject(&projection)?;
+        Ok(Some(projection))
+    }
+
+    fn unproject(&mut self, projection: &Projection) -> Result<(), InputError> {
+        let mut coords: Vec<&mut CoordXYZ> = Vec::new();
+        for island in self.orphans.iter_mut().flatten() {
+            coords.extend(island.cells.iter_mut());
+        }
+        if let Some(deployment) = &mut self.deployment {
+            coords.extend(deployment.generators.iter_mut());
+            coords.extend(deployment.history.iter_mut().flatten());
+        }
+        for zone in self.unassignable.iter_mut().flatten() {
+            coords.extend(zone.cells.iter_mut());
+        }
+        for frontier in self.frontiers.iter_mut().flatten() {
+            coords.push(&mut frontier.centroid);
+            coords.extend(frontier.cells.iter_mut());
+        }
+        for target in self.targets.iter_mut().flatten() {
+            coords.extend(target.target.as_mut());
+        }
+        if let Some(path) = &mut self.path {
+            coords.extend(path.waypoints.iter_mut());
+        }
+        if let Some(metrics) = &mut self.metrics {
+            coords.extend(metrics.uncovered.iter_mut());
+            coords.extend(metrics.overlapping.iter_mut());
+        }
+        for border in self.borders.iter_mut().flatten() {
+            coords.push(&mut border.midpoint);
+            coords.extend(border.polylines.iter_mut().flatten());
+        }
+        if let Some(rendezvous) = &mut self.rendezvous {
+            coords.extend(rendezvous.pairs.iter_mut().map(|pair| &mut pair.cell));
+            coords.extend(rendezvous.base.iter_mut().map(|base| &mut base.cell));
+        }
+        if let Some(redistribution) = &mut self.redistribution {
+            coords.extend(redistribution.stranded.iter_mut());
+        }
+
+        coords
+            .into_iter()
+            .try_for_each(|coord| coord.unproject(projection))
+    }
+}
+
+/// A `nav_msgs/OccupancyGrid` message.
+#[derive(Serialize)]
+pub struct OutputOccupancyGrid {
+    pub(super) header: OutputGridHeader,
+    pub(super) info: OutputGridInfo,
+    /// The occupancy of each cell in percent, in row-major order starting with
+    /// the bottom row.
+    pub(super) data: Vec<i8>,
+}
+
+#[derive(Serialize)]
+pub struct OutputGridHeader {
+    pub(super) frame_id: String,
+}
+
+#[derive(Serialize)]
+pub struct OutputGridInfo {
+    resolution: f64,
+    width: usize,
+    height: usize,
+    origin: OutputPose,
+}
+
+#[derive(Serialize)]
+pub struct OutputPose {
+    position: CoordXYZ,
+    orientation: OutputQuaternion,
+}
+
+#[derive(Serialize)]
+pub struct OutputQuaternion {
+    x: f64,
+    y: f64,
+    z: f64,
+    w: f64,
+}
+
+impl OutputGridInfo {
+    pub(super) fn new(resolution: f64, width: usize, height: usize, (x, y): (f64, f64)) -> Self {
+        Self {
+            resolution,
+            width,
+            height,
+            origin: OutputPose {
+                position: CoordXYZ { x, y, z: 0.0 },
+                // The grid is never rotated
+                orientation: OutputQuaternion {
+                    x: 0.0,
+                    y: 0.0,
+                    z: 0.0,
+                    w: 1.0,
+                },
+            },
+        }
+    }
+}
+
+#[derive(Serialize)]
+pub struct OutputZone {
+    pub(super) name: String,
+    /// The zone's unassignable cells in real-world coordinates.
+    pub(super) cells: Vec<CoordXYZ>,
+}
+
+#[derive(Serialize)]
+pub struct OutputFrontier {
+    /// Index of the robot the segment is assigned to, `0` being `me` followed
+    /// by `others` in order.
+    robot: Option<usize>,
+    centroid: CoordXYZ,
+    size: usize,
+    /// The segment's cells in real-world coordinates.
+    cells: Vec<CoordXYZ>,
+}
+
+#[derive(Serialize)]
+pub struct OutputTarget {
+    /// Index of the frontier segment, if the robot got any.
+    segment: Option<usize>,
+    /// The cell to head to in real-world coordinates.
+    target: Option<CoordXYZ>,
+    /// The travel cost reduced by the information gain.
+    cost: Option<f64>,
+}
+
+/// The response of the frontier targets endpoint.
+#[derive(Serialize)]
+pub struct OutputTargets {
+    pub(super) targets: Vec<OutputTarget>,
+    pub(super) frontiers: Vec<OutputFrontier>,
+}
+
+/// A coverage path, which is also the response of the coverage path endpoint.
+#[derive(Serialize)]
+pub struct OutputPath {
+    /// Index of the robot, `0` being `me` followed by `others` in order.
+    robot: usize,
+    /// The length of the path in the local frame's units.
+    length: f64,
+    /// The waypoints in real-world coordinates, in order.
+    waypoints: Vec<CoordXYZ>,
+}
+
+#[derive(Serialize)]
+pub struct OutputCompletion {
+    /// The longest completion time of all robots.
+    pub(super) makespan: f64,
+    /// The estimate of each robot, `me` first followed by `others` in order.
+    pub(super) robots: Vec<Completion>,
+}
+
+/// The quality of a partition, which is also the response of the evaluation
+/// endpoint.
+#[derive(Serialize)]
+pub struct OutputMetrics {
+    robots: Vec<RegionMetrics>,
+    imbalance: Option<f64>,
+    /// Cells to be covered but owned by nobody, in real-world coordinates.
+    uncovered: Vec<CoordXYZ>,
+    /// Cells owned by several robots, in real-world coordinates.
+    overlapping: Vec<CoordXYZ>,
+}
+
+/// The response of the consistency endpoint. All cells are in the matrix
+/// coordinates of the results, which are in the same order as the robots.
+#[derive(Serialize)]
+pub struct OutputConsistency {
+    /// Cells claimed by several robots.
+    overlapping: Vec<OutputOverlap>,
+    /// Cells to be covered which nobody claims.
+    orphaned: Vec<CoordXYZ>,
+    /// The pairs of robots claiming the same cells.
+    conflicts: Vec<Conflict>,
+    /// The cells of each robot after reconciling, if requested.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    reconciled: Option<Vec<Vec<CoordXYZ>>>,
+}
+
+#[derive(Serialize)]
+pub struct OutputOverlap {
+    cell: CoordXYZ,
+    robots: Vec<usize>,
+}
+
+#[derive(Serialize)]
+pub struct OutputRedistribution {
+    /// The robots which gained cells and how many.
+    gained: Vec<Handover>,
+    /// Cells which none of the neighbours could take over, in real-world
+    /// coordinates.
+    stranded: Vec<CoordXYZ>,
+}
+
+#[derive(Serialize)]
+pub struct OutputJoining {
+    /// The number of cells which changed hands, all to the newcomer.
+    pub(super) changed: usize,
+    /// The robots which lost cells and how many.
+    pub(super) lost: Vec<Handover>,
+}
+
+#[derive(Serialize)]
+pub struct OutputBorder {
+    /// The indices of both robots, the lower one first.
+    robots: (usize, usize),
+    length: f64,
+    midpoint: CoordXYZ,
+    /// The connected pieces of the border in real-world coordinates, each
+    /// ordered from one end to the other.
+    polylines: Vec<Vec<CoordXYZ>>,
+}
+
+/// The meeting points of the robots, which are also the response of the
+/// rendezvous endpoint.
+#[derive(Serialize)]
+pub struct OutputRendezvous {
+    /// One meeting point per pair of neighbouring robots.
+    pairs: Vec<OutputMeeting>,
+    /// How long each robot needs to get to the base station, if there is one.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    base: Option<OutputBase>,
+}
+
+#[derive(Serialize)]
+pub struct OutputBase {
+    /// The cell holding the base station in real-world coordinates.
+    cell: CoordXYZ,
+    /// One visit per robot, `me` first followed by `others` in order.
+    visits: Vec<BaseVisit>,
+}
+
+#[derive(Serialize)]
+pub struct OutputMeeting {
+    /// The indices of both robots, the lower one first.
+    robots: (usize, usize),
+    /// The cell to meet at in real-world coordinates.
+    cell: CoordXYZ,
+    /// The travel time of each robot.
+    times: (f64, f64),
+}
+
+/// My buffer, both as a mask of the map's cells and in real-world coordinates.
+pub(super) struct OutputBuffer {
+    cells: Array2<bool>,
+    locations: Vec<CoordXYZ>,
+}
+
+#[derive(Serialize)]
+pub struct OutputIsland {
+    /// Index of the robot which previously owned the island, `0` being `me`
+    /// followed by `others` in order.
+    pub(super) robot: usize,
+    /// The island's cells in real-world coordinates.
+    pub(super) cells: Vec<CoordXYZ>,
+}
+
+#[derive(Serialize)]
+pub struct OutputDeployment {
+    converged: bool,
+    /// The converged position of each robot, `me` first followed by `others`
+    /// in order.
+    generators: Vec<CoordXYZ>,
+    /// The robots' positions after every iteration.
+    history: Vec<Vec<CoordXYZ>>,
+}
+
+impl OutputDeployment {
+    pub(super) fn new(deployment: Deployment) -> Self {
+        Self {
+            converged: deployment.converged,
+            generators: deployment.generators.iter().map(|p| p.into()).collect(),
+            history: deployment
+                .history
+                .iter()
+                .map(|positions| positions.iter().map(|p| p.into()).collect())
+                .collect(),
+        }
+    }
+}
+
+impl OutputZone {
+    pub(super) fn new(name: String, cells: Vec<(usize, usize)>, frame: &GridFrame) -> Self {
+        Self {
+            name,
+            cells: cells
+                .into_iter()
+                .map(|(row, col)| (&frame.location(row, col)).into())
+                .collect(),
+        }
+    }
+}
+
+impl OutputFrontier {
+    pub(super) fn new(segment: FrontierSegment, frame: &GridFrame) -> Self {
+        Self {
+            robot: segment.robot,
+            centroid: (&segment.centroid).into(),
+            size: segment.cells.len(),
+            cells: segment
+                .cells
+                .into_iter()
+                .map(|(row, col)| (&frame.location(row, col)).into())
+                .collect(),
+        }
+    }
+}
+
+impl OutputTarget {
+    pub(super) fn new(target: Option<Target>, frame: &GridFrame) -> Self {
+        match target {
+            Some(target) => Self {
+                segment: Some(target.segment),
+                target: Some((&frame.location(target.cell.0, target.cell.1)).into()),
+                cost: Some(target.cost),
+            },
+            None => Self {
+                segment: None,
+                target: None,
+                cost: None,
+            },
+        }
+    }
+}
+
+impl OutputPath {
+    pub(super) fn new(robot: usize, waypoints: Vec<RealWorldLocation>) -> Self {
+        let length = waypoints
+            .windows(2)
+            .map(|pair| (pair[1].x() - pair[0].x()).hypot(pair[1].y() - pair[0].y()))
+            .sum();
+        Self {
+            robot,
+            length,
+            waypoints: waypoints.iter().map(|w| w.into()).collect(),
+        }
+    }
+}
+
+impl OutputMetrics {
+    pub(super) fn new(metrics: PartitionMetrics, frame: &GridFrame) -> Self {
+        let locate = |cells: Vec<(usize, usize)>| {
+            cells
+                .into_iter()
+                .map(|(row, col)| (&frame.location(row, col)).into())
+                .collect()
+        };
+        Self {
+            robots: metrics.robots,
+            imbalance: metrics.imbalance,
+            uncovered: locate(metrics.uncovered),
+            overlapping: locate(metrics.overlapping),
+        }
+    }
+}
+
+impl OutputConsistency {
+    pub(super) fn new(consistency: Consistency, robots: usize, frame: &GridFrame) -> Self {
+        let locate = |(row, col): (usize, usize)| (&frame.location(row, col)).into();
+        Self {
+            overlapping: consistency
+                .overlapping
+                .into_iter()
+                .map(|(cell, robots)| OutputOverlap {
+                    cell: locate(cell),
+                    robots,
+                })
+                .collect(),
+            orphaned: consistency.orphaned.into_iter().map(locate).collect(),
+            conflicts: consistency.conflicts,
+            reconciled: consistency.reconciled.map(|ownership| {
+                (0..robots)
+                    .map(|robot| ownership.cells_of(robot).into_iter().map(locate).collect())
+                    .collect()
+            }),
+        }
+    }
+}
+
+impl OutputRedistribution {
+    pub(super) fn new(
+        gained: Vec<Handover>,
+        stranded: Vec<(usize, usize)>,
+        frame: &GridFrame,
+    ) -> Self {
+        Self {
+            gained,
+            stranded: stranded
+                .into_iter()
+                .map(|(row, col)| (&frame.location(row, col)).into())
+                .collect(),
+        }
+    }
+}
+
+impl OutputBorder {
+    pub(super) fn new(border: Border) -> Self {
+        Self {
+            robots: border.robots,
+            length: border.length,
+            midpoint: (&border.midpoint).into(),
+            polylines: border
+                .polylines
+                .iter()
+                .map(|polyline| polyline.iter().map(|p| p.into()).collect())
+                .collect(),
+        }
+    }
+}
+
+impl OutputRendezvous {
+    pub(super) fn new(meetings: Meetings, frame: &GridFrame) -> Self {
+        Self {
+            pairs: meetings
+                .pairs
+                .into_iter()
+                .map(|pair| OutputMeeting {
+                    robots: pair.robots,
+                    cell: (&frame.location(pair.cell.0, pair.cell.1)).into(),
+                    times: pair.times,
+                })
+                .collect(),
+            base: meetings.base.map(|base| OutputBase {
+                cell: (&frame.location(base.cell.0, base.cell.1)).into(),
+                visits: base.visits,
+            }),
+        }
+    }
+}
+
+impl OutputBuffer {
+    pub(super) fn new(cells: Array2<bool>, frame: &GridFrame) -> Self {
+        Self {
+            locations: cells
+                .indexed_iter()
+                .filter(|(_, inside)| **inside)
+                .map(|((row, col), _)| (&frame.location(row, col)).into())
+                .collect(),
+            cells,
+        }
+    }
+}
+
+impl OutputIsland {
+    pub(super) fn new(island: Island, frame: &GridFrame) -> Self {
+        Self {
+            robot: island.robot,
+            cells: island
+                .cells
+                .into_iter()
+                .map(|(row, col)| (&frame.location(row, col)).into())
+                .collect(),
+        }
+    }
 }
 
 impl OutputData {
-    pub(super) fn from_cellmap(map: &CellMap) -> Self {
+    /// Creates a new [`OutputData`] holding all cells of the map.
+    ///
+    /// Cells which are `blocked` are labelled with their [`Blockage`] rather
+    /// than their [`LocationType`], unless they lie outside of the map.
+    pub(super) fn from_cellmap(map: &CellMap, blocked: Option<&Array2<Option<Blockage>>>) -> Self {
         Self {
             cells: map
                 .cells()
@@ -90,12 +1062,17 @@ impl OutputData {
                             y: row as f64,
                             z: 0.0,
                         },
-                        e.into(),
+                        match blocked.and_then(|blocked| blocked[[row, col]]) {
+                            Some(blockage) if *e != LocationType::OutOfMap => blockage.label(),
+                            _ => e.into(),
+                        },
                     )
                 })
                 .collect(),
             offset: map.offset().into(),
             resolution: map.resolution().into(),
+            report: Report::default(),
+            matrix: true,
         }
     }
 
@@ -112,6 +1089,116 @@ impl OutputData {
             cells,
             offset,
             resolution,
+            report: Report::default(),
+            matrix: false,
+        }
+    }
+
+    /// Attach the [`Report`] gathered while partitioning.
+    ///
+    /// All real-world coordinates are converted back to the input's reference
+    /// system. Matrix coordinates are kept as is and remain metric: their
+    /// `offset` is converted to UTM (in the zone containing the lower corner of
+    /// the map for WGS84 input) to match the `resolution` in metres.
+    ///
+    /// Cells in matrix coordinates which belong to my buffer are labelled
+    /// `"Buffer"`, setting them apart from my core cells. Outputs in
+    /// real-world coordinates only hold some of the cells, hence the cells of
+    /// my buffer are added to them with that label.
+    ///
+    /// # Errors
+    ///
+    /// This function will return [`InputError::InvalidCrs`] if any coordinate
+    /// could not be converted back.
+    pub(super) fn with_report(mut self, mut report: Report) -> Result<Self, InputError> {
+        if let Some(buffer) = report.buffer.take() {
+            if self.matrix {
+                for (coord, label) in &mut self.cells {
+                    if matches!(coord.matrix_index(), Some(index) if buffer.cells[index]) {
+                        *label = "Buffer";
+                    }
+                }
+            } else {
+                self.cells
+                    .extend(buffer.locations.into_iter().map(|coord| (coord, "Buffer")));
+            }
+        }
+        if let Some(projection) = report.restore_crs()? {
+            if self.matrix {
+                self.offset.shift_to_utm(&projection);
+            } else {
+                for (coord, _) in &mut self.cells {
+                    coord.unproject(&projection)?;
+                }
+            }
+        }
+        self.report = report;
+        Ok(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn input(me: (f64, f64), areas: bool) -> InputData {
+        let square = |x: f64, y: f64| {
+            serde_json::json!([
+                { "x": x, "y": y, "z": 0.0 },
+                { "x": x + 1.0, "y": y, "z": 0.0 },
+                { "x": x + 1.0, "y": y + 1.0, "z": 0.0 },
+                { "x": x, "y": y + 1.0, "z": 0.0 },
+            ])
+        };
+        let mut json = serde_json::json!({
+            "crs": { "type": "wgs84" },
+            "vertices": square(8.0, 47.0),
+            "resolution": { "x": 1.0, "y": 1.0, "z": 1.0 },
+            "me": { "position": { "x": me.0, "y": me.1, "z": 0.0 } },
+            "others": [],
+        });
+        if areas {
+            json["areas"] = serde_json::json!([
+                { "vertices": square(6.5, 46.5) },
+                { "vertices": square(5.0, 48.0) },
+            ]);
         }
+        serde_json::from_value(json).expect("The input is valid")
+    }
+
+    #[test]
+    fn reference_is_shared_by_all_robots() {
+        // Both robots lie in different UTM zones
+        let mine = input((8.5, 47.5), false).reference();
+        let theirs = input((5.9, 47.5), false).reference();
+
+        assert_eq!(mine, Some((8.0, 47.0)));
+        assert_eq!(mine, theirs);
+    }
+
+    #[test]
+    fn reference_spans_all_areas() {
+        assert_eq!(input((6.0, 47.0), true).reference(), Some((5.0, 46.5)));
+    }
+
+    #[test]
+    fn trajectories_must_be_finite() {
+        // JSON has no infinite numbers, they only arise e.g. from projecting
+        let pose = |time: f64| InputPose {
+            time,
+            position: CoordXYZ {
+                x: 1.0,
+                y: 2.0,
+                z: 0.0,
+            },
+            heading: 0.0,
+        };
+        let circle = |radius: f64, fov: Option<f64>| InputFootprint::Circle { radius, fov };
+
+        assert!(pose(1.0).is_valid());
+        assert!(!pose(f64::INFINITY).is_valid());
+        assert!(circle(1.0, Some(1.0)).is_valid());
+        assert!(!circle(-1.0, None).is_valid());
+        assert!(!circle(1.0, Some(f64::NAN)).is_valid());
     }
 }